@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 
 use util::bit_set::BitSet;
 
-use crate::{Component, Components, Entity, IntoView, View};
+use crate::{Access, Component, ComponentTypeId, Components, Entity, IntoView, View};
 
 // Read
 #[derive(Debug, Clone, Copy)]
@@ -34,6 +34,10 @@ impl<'a, T: Component> View<'a> for Read<T> {
                 .expect("failed to cast ReadView")
         }
     }
+
+    fn access(access: &mut Access<ComponentTypeId>) {
+        access.add_read(ComponentTypeId::of::<T>());
+    }
 }
 
 // Write
@@ -66,6 +70,10 @@ impl<'a, T: Component> View<'a> for Write<T> {
                 .expect("failed to cast WriteView")
         }
     }
+
+    fn access(access: &mut Access<ComponentTypeId>) {
+        access.add_write(ComponentTypeId::of::<T>());
+    }
 }
 
 // TryRead
@@ -98,6 +106,10 @@ impl<'a, T: Component> View<'a> for TryRead<T> {
                 .and_then(|ptr| ptr.cast::<T>().as_ref())
         }
     }
+
+    fn access(access: &mut Access<ComponentTypeId>) {
+        access.add_read(ComponentTypeId::of::<T>());
+    }
 }
 
 // TryWrite
@@ -129,4 +141,32 @@ impl<'a, T: Component> View<'a> for TryWrite<T> {
                 .and_then(|ptr| ptr.cast::<T>().as_mut())
         }
     }
+
+    fn access(access: &mut Access<ComponentTypeId>) {
+        access.add_write(ComponentTypeId::of::<T>());
+    }
+}
+
+// NonSend
+#[derive(Debug, Clone, Copy)]
+pub struct NonSend<T>(PhantomData<*const T>);
+impl<T> Default for NonSend<T> {
+    fn default() -> Self {
+        NonSend(PhantomData)
+    }
+}
+
+unsafe impl<T> Send for NonSend<T> {}
+unsafe impl<T> Sync for NonSend<T> {}
+
+// NonSendMut
+#[derive(Debug, Clone, Copy)]
+pub struct NonSendMut<T>(PhantomData<*const T>);
+impl<T> Default for NonSendMut<T> {
+    fn default() -> Self {
+        NonSendMut(PhantomData)
+    }
 }
+
+unsafe impl<T> Send for NonSendMut<T> {}
+unsafe impl<T> Sync for NonSendMut<T> {}
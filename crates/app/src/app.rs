@@ -1,4 +1,8 @@
-use crate::{Events, ParRunnable, Resource, Resources, Schedule, Stage, StageLabel, World};
+use crate::{
+    AppLabel, BoxedAppLabel, Component, Events, ParRunnable, Plugin, PluginGroup,
+    PluginGroupBuilder, Reflect, Resource, Resources, Schedule, Stage, StageLabel, TypeRegistry,
+    World,
+};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub enum AppStage {
@@ -8,6 +12,9 @@ pub enum AppStage {
     Update,
     PostUpdate,
     End,
+    /// Run once by `App::shutdown`, when the app is about to exit. Not part of
+    /// `App::schedule`.
+    Shutdown,
 }
 impl StageLabel for AppStage {
     fn dyn_clone(&self) -> Box<dyn StageLabel> {
@@ -18,21 +25,70 @@ impl StageLabel for AppStage {
             AppStage::Update => Box::new("App:Update"),
             AppStage::PostUpdate => Box::new("App:PostUpdate"),
             AppStage::End => Box::new("App:End"),
+            AppStage::Shutdown => Box::new("App:Shutdown"),
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct AppExit;
+/// Ask the runner to exit the process with the exit code, once the shutdown
+/// stage ran.
+#[derive(Debug, Clone, Default)]
+pub struct AppExit {
+    pub code: i32,
+}
+
+impl AppExit {
+    pub fn with_code(code: i32) -> Self {
+        AppExit { code }
+    }
+}
 
-pub trait Plugin {
-    fn build(&mut self, app: &mut App);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum PluginsState {
+    Adding,
+    Finished,
+    Cleaned,
+}
+
+type ExtractFn = Box<dyn FnMut(&mut World, &mut Resources, &mut App)>;
+
+/// An app with its own world, schedule and resources, updated after the main
+/// app once its extract step pulled what it needs from the main world.
+///
+/// Sub apps run on the calling thread, in order, right after the main
+/// schedule: they separate state, not work. Streaming a level off the main
+/// update means spawning the loading work on another thread and extracting its
+/// results here.
+pub struct SubApp {
+    pub app: App,
+    extract: ExtractFn,
+}
+
+impl SubApp {
+    pub fn new(
+        app: App,
+        extract: impl FnMut(&mut World, &mut Resources, &mut App) + 'static,
+    ) -> Self {
+        SubApp {
+            app,
+            extract: Box::new(extract),
+        }
+    }
+
+    pub fn extract(&mut self, world: &mut World, resources: &mut Resources) {
+        (self.extract)(world, resources, &mut self.app);
+    }
 }
 
 pub struct App {
     pub world: World,
     pub schedule: Schedule,
     pub resources: Resources,
+    shutdown: Schedule,
+    shut_down: bool,
+    plugins: Vec<Box<dyn Plugin>>,
+    plugins_state: PluginsState,
+    sub_apps: Vec<(BoxedAppLabel, SubApp)>,
     runner: Box<dyn Fn(App)>,
 }
 
@@ -42,6 +98,11 @@ impl Default for App {
             world: Default::default(),
             resources: Default::default(),
             schedule: Default::default(),
+            shutdown: Default::default(),
+            shut_down: false,
+            plugins: Vec::new(),
+            plugins_state: PluginsState::Adding,
+            sub_apps: Vec::new(),
             runner: Box::new(run_once),
         }
     }
@@ -57,27 +118,143 @@ impl App {
             .add_stage(AppStage::PostUpdate, Stage::sequence())
             .add_stage(AppStage::End, Stage::sequence())
             .add_event::<AppExit>();
+        app.shutdown
+            .add_stage(AppStage::Shutdown, Stage::sequence());
         app
     }
 
-    pub fn add_plugin<P>(&mut self, mut plugin: P) -> &mut Self
+    pub fn add_plugin<P>(&mut self, plugin: P) -> &mut Self
     where
         P: Plugin,
     {
+        self.add_boxed_plugin(Box::new(plugin))
+    }
+
+    pub fn add_plugins<G>(&mut self, mut group: G) -> &mut Self
+    where
+        G: PluginGroup,
+    {
+        let mut builder = PluginGroupBuilder::default();
+        group.build(&mut builder);
+        for plugin in builder.into_plugins() {
+            self.add_boxed_plugin(plugin);
+        }
+        self
+    }
+
+    fn add_boxed_plugin(&mut self, mut plugin: Box<dyn Plugin>) -> &mut Self {
+        if self.plugins_state != PluginsState::Adding {
+            panic!(
+                "Plugin '{}' was added after the plugins were finished",
+                plugin.name()
+            );
+        }
+        if plugin.is_unique() && self.has_plugin(plugin.name()) {
+            panic!("Plugin '{}' was already added", plugin.name());
+        }
+        for dependency in plugin.dependencies() {
+            if !self.has_plugin(dependency) {
+                panic!(
+                    "Plugin '{}' depends on '{}', which must be added first",
+                    plugin.name(),
+                    dependency
+                );
+            }
+        }
+
         plugin.build(self);
+        self.plugins.push(plugin);
+        self
+    }
+
+    pub fn has_plugin(&self, name: &str) -> bool {
+        self.plugins.iter().any(|plugin| plugin.name() == name)
+    }
+
+    /// Run `Plugin::finish` of every plugin, in the order they were added.
+    pub fn finish(&mut self) {
+        if self.plugins_state >= PluginsState::Finished {
+            return;
+        }
+        self.plugins_state = PluginsState::Finished;
+
+        let mut plugins = std::mem::take(&mut self.plugins);
+        for plugin in plugins.iter_mut() {
+            plugin.finish(self);
+        }
+        self.plugins = plugins;
+
+        for (_, sub_app) in self.sub_apps.iter_mut() {
+            sub_app.app.finish();
+        }
+    }
+
+    /// Run `Plugin::cleanup` of every plugin, finishing them first if needed.
+    pub fn cleanup(&mut self) {
+        self.finish();
+        if self.plugins_state >= PluginsState::Cleaned {
+            return;
+        }
+        self.plugins_state = PluginsState::Cleaned;
+
+        let mut plugins = std::mem::take(&mut self.plugins);
+        for plugin in plugins.iter_mut() {
+            plugin.cleanup(self);
+        }
+        self.plugins = plugins;
+
+        for (_, sub_app) in self.sub_apps.iter_mut() {
+            sub_app.app.cleanup();
+        }
+    }
+
+    pub fn add_sub_app(
+        &mut self,
+        label: impl AppLabel,
+        app: App,
+        extract: impl FnMut(&mut World, &mut Resources, &mut App) + 'static,
+    ) -> &mut Self {
+        let label = label.dyn_clone();
+        if self.get_sub_app_index(label.as_ref()).is_some() {
+            panic!("Sub app already exists: {:?}", label);
+        }
+        self.sub_apps.push((label, SubApp::new(app, extract)));
         self
     }
 
+    pub fn sub_app(&self, label: impl AppLabel) -> Option<&App> {
+        self.get_sub_app_index(label.dyn_clone().as_ref())
+            .map(|index| &self.sub_apps[index].1.app)
+    }
+
+    pub fn sub_app_mut(&mut self, label: impl AppLabel) -> Option<&mut App> {
+        self.get_sub_app_index(label.dyn_clone().as_ref())
+            .map(move |index| &mut self.sub_apps[index].1.app)
+    }
+
+    fn get_sub_app_index(&self, label: &dyn AppLabel) -> Option<usize> {
+        self.sub_apps
+            .iter()
+            .position(|(sub_app_label, _)| &**sub_app_label == label)
+    }
+
     pub fn add_resource<R: Resource>(&mut self, resource: R) -> &mut Self {
         self.resources.insert(resource);
         self
     }
 
-    pub fn add_event<T: 'static>(&mut self) -> &mut Self {
+    pub fn add_event<T: Send + Sync + 'static>(&mut self) -> &mut Self {
         self.add_resource(Events::<T>::default())
             .add_system(Events::<T>::update_sys())
     }
 
+    pub fn register_component<T: Component + Reflect + Default>(&mut self) -> &mut Self {
+        self.resources
+            .get_mut_or_default::<TypeRegistry>()
+            .register::<T>();
+        self
+    }
+
     pub fn add_stage(&mut self, label: impl StageLabel, stage: Stage) -> &mut Self {
         self.schedule.add_stage(label, stage);
         self
@@ -107,8 +284,12 @@ impl App {
     where
         S: ParRunnable + 'static,
     {
-        if system.stage().is_some() {
-            self.schedule.add_system(system);
+        if let Some(stage) = system.stage() {
+            if *stage == *AppStage::Shutdown.dyn_clone() {
+                self.shutdown.add_system(system);
+            } else {
+                self.schedule.add_system(system);
+            }
         } else {
             self.add_system_to_stage(AppStage::Update, system);
         }
@@ -119,7 +300,11 @@ impl App {
     where
         S: ParRunnable + 'static,
     {
-        self.schedule.add_system_to_stage(label, system);
+        if *label.dyn_clone() == *AppStage::Shutdown.dyn_clone() {
+            self.shutdown.add_system_to_stage(label, system);
+        } else {
+            self.schedule.add_system_to_stage(label, system);
+        }
         self
     }
 
@@ -128,11 +313,31 @@ impl App {
         self
     }
 
+    /// Run the schedule, then extract and update every sub app in the order they
+    /// were added, on the current thread.
     pub fn update(&mut self) {
         self.schedule.run(&mut self.world, &mut self.resources);
+        for (_, sub_app) in self.sub_apps.iter_mut() {
+            sub_app.extract(&mut self.world, &mut self.resources);
+            sub_app.app.update();
+        }
+    }
+
+    /// Run the shutdown stage of the app and of its sub apps, only the first
+    /// call does anything. Runners call it before exiting.
+    pub fn shutdown(&mut self) {
+        if self.shut_down {
+            return;
+        }
+        self.shut_down = true;
+        self.shutdown.run(&mut self.world, &mut self.resources);
+        for (_, sub_app) in self.sub_apps.iter_mut() {
+            sub_app.app.shutdown();
+        }
     }
 
     pub fn run(&mut self) {
+        self.cleanup();
         let mut app = std::mem::replace(self, App::default());
         let runner = std::mem::replace(&mut app.runner, Box::new(run_once));
         (runner)(app);
@@ -141,4 +346,204 @@ impl App {
 
 fn run_once(mut app: App) {
     app.update();
+    app.shutdown();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        any::type_name,
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    struct First(Log);
+    struct Second(Log);
+    struct Third(Log);
+
+    impl Plugin for First {
+        fn build(&mut self, _app: &mut App) {
+            self.0.lock().unwrap().push("build first".to_string());
+        }
+
+        fn finish(&mut self, _app: &mut App) {
+            self.0.lock().unwrap().push("finish first".to_string());
+        }
+
+        fn cleanup(&mut self, _app: &mut App) {
+            self.0.lock().unwrap().push("cleanup first".to_string());
+        }
+    }
+
+    impl Plugin for Second {
+        fn build(&mut self, _app: &mut App) {
+            self.0.lock().unwrap().push("build second".to_string());
+        }
+
+        fn dependencies(&self) -> Vec<&'static str> {
+            vec![type_name::<First>()]
+        }
+
+        fn finish(&mut self, _app: &mut App) {
+            self.0.lock().unwrap().push("finish second".to_string());
+        }
+    }
+
+    impl Plugin for Third {
+        fn build(&mut self, _app: &mut App) {
+            self.0.lock().unwrap().push("build third".to_string());
+        }
+    }
+
+    struct Group(Log);
+
+    impl PluginGroup for Group {
+        fn build(&mut self, group: &mut PluginGroupBuilder) {
+            group
+                .add(Second(self.0.clone()))
+                .add(Third(self.0.clone()))
+                .add(First(self.0.clone()));
+        }
+    }
+
+    #[test]
+    fn plugin_group() {
+        let log = Log::default();
+        let mut app = App::new();
+        app.add_plugins(Group(log.clone()));
+        app.cleanup();
+
+        assert!(app.has_plugin(type_name::<Third>()));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "build first",
+                "build second",
+                "build third",
+                "finish first",
+                "finish second",
+                "cleanup first",
+            ]
+        );
+    }
+
+    #[test]
+    fn plugin_group_disable_and_replace() {
+        struct Configured(Log);
+        impl Plugin for Configured {
+            fn build(&mut self, _app: &mut App) {
+                self.0.lock().unwrap().push("build configured".to_string());
+            }
+        }
+
+        struct Custom(Log);
+        impl PluginGroup for Custom {
+            fn build(&mut self, group: &mut PluginGroupBuilder) {
+                Group(self.0.clone()).build(group);
+                group
+                    .disable::<Second>()
+                    .replace::<Third, _>(Configured(self.0.clone()));
+            }
+        }
+
+        let log = Log::default();
+        App::new().add_plugins(Custom(log.clone()));
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["build configured", "build first"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "was already added")]
+    fn duplicate_plugin() {
+        let log = Log::default();
+        App::new()
+            .add_plugin(First(log.clone()))
+            .add_plugin(First(log));
+    }
+
+    #[test]
+    #[should_panic(expected = "must be added first")]
+    fn missing_dependency() {
+        let log = Log::default();
+        App::new()
+            .add_plugin(Second(log.clone()))
+            .add_plugin(First(log));
+    }
+
+    #[test]
+    fn sub_app() {
+        use crate::{Entities, Entity, Query, SystemBuilder};
+
+        struct Sprite(i32);
+        #[derive(Default)]
+        struct Rendered(i32);
+
+        let mut render_app = App::new();
+        render_app.add_resource(Rendered::default()).add_system(
+            SystemBuilder::new()
+                .write_resource::<Rendered>()
+                .with_query(Query::<&Sprite>::new())
+                .build(|world, _, rendered, query| {
+                    rendered.0 += query.iter(world).into_iter().map(|s| s.0).sum::<i32>()
+                }),
+        );
+
+        let mut app = App::new();
+        app.world.spawn().add(Sprite(1));
+        app.world.spawn().add(Sprite(2));
+        app.add_sub_app("render", render_app, |world, _, render_app| {
+            let entities: Vec<Entity> = Query::<(Entities, &Sprite)>::new()
+                .iter(world)
+                .into_iter()
+                .map(|(entity, _)| entity)
+                .collect();
+            for entity in entities {
+                world.move_entity_to(entity, &mut render_app.world);
+            }
+        });
+        app.update();
+        app.update();
+
+        assert!(Query::<&Sprite>::new().iter(&app.world).is_empty());
+        let render_app = app.sub_app("render").unwrap();
+        assert_eq!(render_app.resources.get::<Rendered>().unwrap().0, 6);
+    }
+
+    #[test]
+    fn shutdown() {
+        use crate::SystemBuilder;
+
+        #[derive(Default)]
+        struct Counts {
+            updates: u32,
+            shutdowns: u32,
+        }
+
+        let mut app = App::new();
+        app.add_resource(Counts::default())
+            .add_system(
+                SystemBuilder::new()
+                    .write_resource::<Counts>()
+                    .build(|_, _, counts, _| counts.updates += 1),
+            )
+            .add_system(
+                SystemBuilder::new()
+                    .on_stage(AppStage::Shutdown)
+                    .write_resource::<Counts>()
+                    .build(|_, _, counts, _| counts.shutdowns += 1),
+            );
+        app.update();
+        app.update();
+        app.shutdown();
+        app.shutdown();
+
+        let counts = app.resources.get::<Counts>().unwrap();
+        assert_eq!((counts.updates, counts.shutdowns), (2, 1));
+    }
 }
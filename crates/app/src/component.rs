@@ -1,8 +1,10 @@
 use std::{
+    alloc::{self, Layout},
     any::{type_name, TypeId},
     collections::HashMap,
     fmt::Display,
     hash::Hash,
+    sync::atomic::{AtomicU32, Ordering},
 };
 
 use util::{bit_set::BitSet, blob_sparse_set::BlobSparseSet};
@@ -12,22 +14,28 @@ use crate::entity::Entity;
 pub trait Component: 'static + Send + Sync {}
 impl<T: 'static + Send + Sync> Component for T {}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum ComponentKind {
+    Rust(TypeId),
+    Dynamic(u32),
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialOrd, Ord)]
 pub struct ComponentTypeId {
-    type_id: TypeId,
+    kind: ComponentKind,
     #[cfg(debug_assertions)]
     name: &'static str,
 }
 
 impl Hash for ComponentTypeId {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.type_id.hash(state)
+        self.kind.hash(state)
     }
 }
 
 impl PartialEq for ComponentTypeId {
     fn eq(&self, other: &Self) -> bool {
-        self.type_id.eq(&other.type_id)
+        self.kind.eq(&other.kind)
     }
 }
 
@@ -39,28 +47,87 @@ impl Display for ComponentTypeId {
 
     #[cfg(not(debug_assertions))]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.type_id)
+        write!(f, "{:?}", self.kind)
     }
 }
 
+static NEXT_DYNAMIC_ID: AtomicU32 = AtomicU32::new(0);
+
 impl ComponentTypeId {
     pub fn of<T>() -> Self
     where
         T: Component,
     {
         Self {
-            type_id: TypeId::of::<T>(),
+            kind: ComponentKind::Rust(TypeId::of::<T>()),
             #[cfg(debug_assertions)]
             name: type_name::<T>(),
         }
     }
+
+    /// New id that is not backed by a Rust type, unique for the whole process.
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    fn dynamic(name: &'static str) -> Self {
+        Self {
+            kind: ComponentKind::Dynamic(NEXT_DYNAMIC_ID.fetch_add(1, Ordering::Relaxed)),
+            #[cfg(debug_assertions)]
+            name,
+        }
+    }
+
+    #[inline]
+    pub fn is_dynamic(&self) -> bool {
+        matches!(self.kind, ComponentKind::Dynamic(_))
+    }
+}
+
+/// Component type defined at runtime, such as by a script or a C API.
+#[derive(Debug, Clone)]
+pub struct ComponentDescriptor {
+    name: String,
+    layout: Layout,
+    drop: Option<unsafe fn(*mut u8)>,
+}
+
+impl ComponentDescriptor {
+    /// `drop` is called on a component before its memory is released, `None`
+    /// for plain data.
+    pub fn new(name: impl Into<String>, layout: Layout, drop: Option<unsafe fn(*mut u8)>) -> Self {
+        ComponentDescriptor {
+            name: name.into(),
+            layout,
+            drop,
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
 }
 
+unsafe fn drop_nothing(_ptr: *mut u8) {}
+
 pub type ComponentVec = BlobSparseSet<Entity>;
 
+/// Clone the component behind the first pointer into the uninitialized memory
+/// behind the second one.
+pub type CloneFn = unsafe fn(*const u8, *mut u8);
+
+unsafe fn clone_ptr<T: Clone>(src: *const u8, dst: *mut u8) {
+    dst.cast::<T>().write((*src.cast::<T>()).clone())
+}
+
 #[derive(Default)]
 pub struct Components {
     vecs: HashMap<ComponentTypeId, ComponentVec>,
+    clone_fns: HashMap<ComponentTypeId, CloneFn>,
+    dynamic_ids: HashMap<&'static str, ComponentTypeId>,
 }
 
 impl Components {
@@ -76,6 +143,69 @@ impl Components {
         self.get_vec::<T>().and_then(|vec| vec.get_ptr(entity))
     }
 
+    pub(crate) fn get_ptr_raw(&self, type_id: &ComponentTypeId, entity: Entity) -> Option<*mut u8> {
+        self.vecs.get(type_id).and_then(|vec| vec.get_ptr(entity))
+    }
+
+    /// Number of stored components of every type.
+    pub(crate) fn storage_sizes(&self) -> Vec<(ComponentTypeId, usize)> {
+        self.vecs
+            .iter()
+            .map(|(type_id, vec)| (*type_id, vec.len()))
+            .collect()
+    }
+
+    pub(crate) fn get_bitset_raw(&self, type_id: &ComponentTypeId) -> Option<&BitSet> {
+        self.vecs.get(type_id).map(|set| set.bitset())
+    }
+
+    /// Register a runtime component type. Registering a name twice returns the
+    /// existing id.
+    ///
+    /// # Panics
+    /// If the name is already registered with another layout.
+    pub(crate) fn register_dynamic(&mut self, descriptor: ComponentDescriptor) -> ComponentTypeId {
+        if let Some(type_id) = self.dynamic_ids.get(descriptor.name()) {
+            let layout = self.vecs[type_id].layout();
+            if layout != descriptor.layout {
+                panic!(
+                    "Component '{}' is already registered with {:?}",
+                    descriptor.name, layout
+                );
+            }
+            return *type_id;
+        }
+
+        let name: &'static str = Box::leak(descriptor.name.into_boxed_str());
+        let type_id = ComponentTypeId::dynamic(name);
+        let drop = descriptor.drop.unwrap_or(drop_nothing);
+        self.vecs
+            .insert(type_id, ComponentVec::new(descriptor.layout, drop, 0));
+        self.dynamic_ids.insert(name, type_id);
+        type_id
+    }
+
+    pub(crate) fn dynamic_id(&self, name: &str) -> Option<ComponentTypeId> {
+        self.dynamic_ids.get(name).copied()
+    }
+
+    /// Move the bytes behind `component` into the storage of `type_id`, returns
+    /// false when the type has no storage yet.
+    pub(crate) unsafe fn insert_raw(
+        &mut self,
+        type_id: &ComponentTypeId,
+        entity: Entity,
+        component: *mut u8,
+    ) -> bool {
+        match self.vecs.get_mut(type_id) {
+            Some(vec) => {
+                vec.insert(entity, component);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub(crate) fn insert<T: Component>(&mut self, entity: Entity, component: T) {
         let type_id = ComponentTypeId::of::<T>();
         let vec = if let Some(vec) = self.vecs.get_mut(&type_id) {
@@ -100,6 +230,92 @@ impl Components {
             vec.remove(entity);
         }
     }
+
+    /// Move a component of `entity` into `target` as a component of `target_entity`.
+    pub(crate) fn move_raw(
+        &mut self,
+        type_id: &ComponentTypeId,
+        entity: Entity,
+        target: &mut Components,
+        target_entity: Entity,
+    ) -> bool {
+        if let Some(vec) = self.vecs.get_mut(type_id) {
+            if let Some(ptr) = vec.remove_and_forget(entity) {
+                let target_vec = target
+                    .vecs
+                    .entry(*type_id)
+                    .or_insert_with(|| ComponentVec::new(vec.layout(), vec.drop_fn(), 0));
+                unsafe { target_vec.insert(target_entity, ptr) };
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Drop the whole storage of a component type.
+    pub(crate) fn clear_raw(&mut self, type_id: &ComponentTypeId) {
+        self.vecs.remove(type_id);
+    }
+
+    pub(crate) fn register_clone<T: Component + Clone>(&mut self) {
+        self.clone_fns
+            .insert(ComponentTypeId::of::<T>(), clone_ptr::<T>);
+    }
+
+    pub(crate) fn clone_fn(&self, type_id: &ComponentTypeId) -> Option<CloneFn> {
+        self.clone_fns.get(type_id).copied()
+    }
+
+    /// Clone a component of `entity` into `target` as a component of
+    /// `target_entity`, the clone function is registered on `target` as well.
+    pub(crate) fn clone_raw(
+        &self,
+        type_id: &ComponentTypeId,
+        entity: Entity,
+        target: &mut Components,
+        target_entity: Entity,
+    ) -> bool {
+        let (vec, clone_fn) = match (self.vecs.get(type_id), self.clone_fn(type_id)) {
+            (Some(vec), Some(clone_fn)) => (vec, clone_fn),
+            _ => return false,
+        };
+        let src = match vec.get_ptr(entity) {
+            Some(src) => src,
+            None => return false,
+        };
+
+        let layout = vec.layout();
+        let target_vec = target
+            .vecs
+            .entry(*type_id)
+            .or_insert_with(|| ComponentVec::new(layout, vec.drop_fn(), 0));
+        target.clone_fns.entry(*type_id).or_insert(clone_fn);
+        unsafe {
+            let dst = alloc_temp(layout);
+            clone_fn(src, dst);
+            target_vec.insert(target_entity, dst);
+            dealloc_temp(dst, layout);
+        }
+        true
+    }
+}
+
+unsafe fn alloc_temp(layout: Layout) -> *mut u8 {
+    if layout.size() == 0 {
+        layout.align() as *mut u8
+    } else {
+        let ptr = alloc::alloc(layout);
+        if ptr.is_null() {
+            alloc::handle_alloc_error(layout)
+        }
+        ptr
+    }
+}
+
+unsafe fn dealloc_temp(ptr: *mut u8, layout: Layout) {
+    if layout.size() != 0 {
+        alloc::dealloc(ptr, layout)
+    }
 }
 
 #[cfg(test)]
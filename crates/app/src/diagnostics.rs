@@ -0,0 +1,232 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    App, AppStage, BoxedStageLabel, ComponentTypeId, ParRunnable, Plugin, SystemBuilder, World,
+};
+
+#[derive(Debug, Clone)]
+pub struct SystemTiming {
+    pub name: String,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct StageTiming {
+    pub stage: BoxedStageLabel,
+    pub duration: Duration,
+    /// Systems that ran during the stage, in insertion order.
+    pub systems: Vec<SystemTiming>,
+}
+
+/// Frame statistics of the app. Stage and system timings are only measured
+/// with the `diagnostics` feature.
+pub struct Diagnostics {
+    frame_times: VecDeque<Duration>,
+    max_frames: usize,
+    last_frame: Option<Instant>,
+    frame_count: u64,
+    entity_count: u32,
+    component_counts: Vec<(ComponentTypeId, usize)>,
+    stage_timings: Vec<StageTiming>,
+}
+
+impl Diagnostics {
+    /// Keep the frame times of the last `max_frames` frames.
+    pub fn new(max_frames: usize) -> Self {
+        Diagnostics {
+            frame_times: VecDeque::with_capacity(max_frames),
+            max_frames: max_frames.max(1),
+            last_frame: None,
+            frame_count: 0,
+            entity_count: 0,
+            component_counts: Vec::new(),
+            stage_timings: Vec::new(),
+        }
+    }
+
+    /// Mark the end of a frame and sample the world.
+    pub fn end_frame(&mut self, world: &World) {
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame {
+            if self.frame_times.len() == self.max_frames {
+                self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(now - last_frame);
+        }
+        self.last_frame = Some(now);
+        self.frame_count += 1;
+
+        self.entity_count = world.len();
+        self.component_counts = world.component_counts();
+        self.component_counts
+            .sort_by_cached_key(|(type_id, _)| type_id.to_string());
+    }
+
+    #[inline]
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Average frame time over the rolling window.
+    pub fn frame_time(&self) -> Duration {
+        if self.frame_times.is_empty() {
+            Duration::default()
+        } else {
+            self.frame_times.iter().sum::<Duration>() / self.frame_times.len() as u32
+        }
+    }
+
+    pub fn fps(&self) -> f64 {
+        let frame_time = self.frame_time().as_secs_f64();
+        if frame_time > 0. {
+            1. / frame_time
+        } else {
+            0.
+        }
+    }
+
+    pub fn frame_times(&self) -> impl Iterator<Item = &Duration> {
+        self.frame_times.iter()
+    }
+
+    #[inline]
+    pub fn entity_count(&self) -> u32 {
+        self.entity_count
+    }
+
+    /// Number of stored components of every type, at the end of the last frame.
+    pub fn component_counts(&self) -> &[(ComponentTypeId, usize)] {
+        &self.component_counts
+    }
+
+    /// Timings of the last run of the schedule.
+    pub fn stage_timings(&self) -> &[StageTiming] {
+        &self.stage_timings
+    }
+
+    #[cfg(feature = "diagnostics")]
+    pub(crate) fn set_stage_timings(&mut self, timings: Vec<StageTiming>) {
+        self.stage_timings = timings;
+    }
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Diagnostics::new(120)
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "frame {}: {:.2} ms ({:.1} fps), {} entities",
+            self.frame_count,
+            self.frame_time().as_secs_f64() * 1000.,
+            self.fps(),
+            self.entity_count
+        )?;
+        for (type_id, count) in self.component_counts.iter() {
+            writeln!(f, "  {}: {}", type_id, count)?;
+        }
+        for stage in self.stage_timings.iter() {
+            writeln!(f, "  {:?}: {:?}", stage.stage, stage.duration)?;
+            for system in stage.systems.iter() {
+                writeln!(f, "    {}: {:?}", system.name, system.duration)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add the `Diagnostics` resource, updated at the end of every frame and
+/// printed every `log_interval` if set.
+pub struct DiagnosticsPlugin {
+    pub max_frames: usize,
+    pub log_interval: Option<Duration>,
+}
+
+impl Default for DiagnosticsPlugin {
+    fn default() -> Self {
+        DiagnosticsPlugin {
+            max_frames: 120,
+            log_interval: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.add_resource(Diagnostics::new(self.max_frames))
+            .add_system(diagnostics_sys(self.log_interval));
+    }
+}
+
+fn diagnostics_sys(log_interval: Option<Duration>) -> impl ParRunnable {
+    let mut last_log = Instant::now();
+    SystemBuilder::new()
+        .on_stage(AppStage::End)
+        .write_resource::<Diagnostics>()
+        .build(move |world, _, diagnostics, _| {
+            diagnostics.end_frame(world);
+            if let Some(log_interval) = log_interval {
+                if last_log.elapsed() >= log_interval {
+                    last_log = Instant::now();
+                    tracing::info!("{}", **diagnostics);
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "diagnostics")]
+    use crate::StageLabel;
+
+    struct Foo;
+
+    #[test]
+    fn diagnostics() {
+        let mut app = App::new();
+        app.add_plugin(DiagnosticsPlugin {
+            max_frames: 2,
+            log_interval: None,
+        })
+        .add_system(SystemBuilder::new().build(|world, cmd, _, _| {
+            cmd.spawn(world).add(Foo);
+        }));
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let diagnostics = app.resources.get::<Diagnostics>().unwrap();
+        assert_eq!(diagnostics.frame_count(), 4);
+        assert_eq!(diagnostics.frame_times().count(), 2);
+        assert!(diagnostics.fps() > 0.);
+        // Stages flush their commands when they end, the entity spawned on
+        // `Update` is counted in the same frame.
+        assert_eq!(diagnostics.entity_count(), 4);
+        assert!(diagnostics
+            .component_counts()
+            .contains(&(ComponentTypeId::of::<Foo>(), 4)));
+
+        #[cfg(feature = "diagnostics")]
+        {
+            let update_label = AppStage::Update.dyn_clone();
+            let update = diagnostics
+                .stage_timings()
+                .iter()
+                .find(|timing| *timing.stage == *update_label)
+                .unwrap();
+            assert_eq!(update.systems.len(), 1);
+            assert!(update.systems[0].name.contains("diagnostics::tests"));
+        }
+        #[cfg(not(feature = "diagnostics"))]
+        assert!(diagnostics.stage_timings().is_empty());
+    }
+}
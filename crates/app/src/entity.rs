@@ -5,11 +5,12 @@ use std::{
     sync::atomic::{AtomicI64, Ordering},
 };
 
+use serde::{Deserialize, Serialize};
 use util::{bit_set::BitSet, sparse_set::SparseIndex};
 
 use crate::{Component, ComponentTypeId};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Entity {
     id: u32,
     generation: u32,
@@ -53,6 +54,18 @@ pub struct EntityAllocator {
     len: u32,
 }
 
+impl Clone for EntityAllocator {
+    fn clone(&self) -> Self {
+        EntityAllocator {
+            entries: self.entries.clone(),
+            bitset: self.bitset.clone(),
+            pending: self.pending.clone(),
+            cursor: AtomicI64::new(self.cursor.load(Ordering::Relaxed)),
+            len: self.len,
+        }
+    }
+}
+
 impl EntityAllocator {
     #[inline]
     pub(crate) fn get_entity(&self, id: u32) -> Option<Entity> {
@@ -72,20 +85,28 @@ impl EntityAllocator {
     }
 
     pub(crate) fn add_component<T: Component>(&mut self, entity: Entity) {
+        self.add_component_raw(entity, ComponentTypeId::of::<T>())
+    }
+
+    pub(crate) fn add_component_raw(&mut self, entity: Entity, type_id: ComponentTypeId) {
         if self.is_live(entity) {
             let index = entity.id as usize;
             let components = self.entries[index]
                 .components
                 .get_or_insert(Default::default());
-            components.insert(ComponentTypeId::of::<T>());
+            components.insert(type_id);
         }
     }
 
     pub(crate) fn remove_commponent<T: Component>(&mut self, entity: Entity) {
+        self.remove_component_raw(entity, &ComponentTypeId::of::<T>())
+    }
+
+    pub(crate) fn remove_component_raw(&mut self, entity: Entity, type_id: &ComponentTypeId) {
         if self.is_live(entity) {
             let index = entity.id as usize;
             if let Some(components) = &mut self.entries[index].components {
-                components.remove(&ComponentTypeId::of::<T>());
+                components.remove(type_id);
             }
         }
     }
@@ -179,6 +200,29 @@ impl EntityAllocator {
         }
     }
 
+    pub(crate) fn live_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.bitset
+            .iter()
+            .filter_map(move |id| self.get_entity(id as u32))
+    }
+
+    /// Keep only the component types matching `f` in every entity entry.
+    pub(crate) fn retain_components(&mut self, mut f: impl FnMut(&ComponentTypeId) -> bool) {
+        for entry in self.entries.iter_mut() {
+            if let Some(components) = &mut entry.components {
+                components.retain(|type_id| f(type_id));
+            }
+        }
+    }
+
+    pub(crate) fn components_of(&self, entity: Entity) -> Option<&HashSet<ComponentTypeId>> {
+        if self.is_live(entity) {
+            self.entries[entity.id as usize].components.as_ref()
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn is_live(&self, entity: Entity) -> bool {
         let index = entity.id as usize;
         index < self.entries.len()
@@ -55,7 +55,10 @@ impl<T: 'static> Events<T> {
         }
     }
 
-    pub(crate) fn update_sys() -> impl ParRunnable {
+    pub(crate) fn update_sys() -> impl ParRunnable
+    where
+        T: Send,
+    {
         SystemBuilder::new()
             .on_stage(AppStage::Begin)
             .write_resource::<Events<T>>()
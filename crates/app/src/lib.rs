@@ -1,17 +1,31 @@
 pub mod accessor;
 pub mod app;
 pub mod component;
+pub mod diagnostics;
 pub mod entity;
 pub mod event;
+pub mod plugin;
+pub mod prefab;
 pub mod query;
+pub mod reflect;
+pub mod scene;
 pub mod system;
+#[cfg(feature = "trace_chrome")]
+pub mod trace;
 pub mod world;
 
 pub use accessor::*;
 pub use app::*;
 pub use component::*;
+pub use diagnostics::*;
 pub use entity::*;
 pub use event::*;
+pub use plugin::*;
+pub use prefab::*;
 pub use query::*;
+pub use reflect::*;
+pub use scene::*;
 pub use system::*;
+#[cfg(feature = "trace_chrome")]
+pub use trace::*;
 pub use world::*;
@@ -0,0 +1,161 @@
+use std::{
+    any::{type_name, TypeId},
+    collections::HashMap,
+};
+
+use crate::App;
+
+pub trait Plugin: 'static {
+    fn build(&mut self, app: &mut App);
+
+    /// Unique name of the plugin, used for duplicate and dependency checks.
+    fn name(&self) -> &str {
+        type_name::<Self>()
+    }
+
+    /// Names of the plugins that must be built before this one.
+    fn dependencies(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// Whether adding the plugin a second time is an error.
+    fn is_unique(&self) -> bool {
+        true
+    }
+
+    /// Called once every plugin has been built.
+    fn finish(&mut self, _app: &mut App) {}
+
+    /// Called after every plugin finished, right before the app starts running.
+    fn cleanup(&mut self, _app: &mut App) {}
+}
+
+pub trait PluginGroup {
+    fn build(&mut self, group: &mut PluginGroupBuilder);
+}
+
+struct PluginEntry {
+    plugin: Box<dyn Plugin>,
+    enabled: bool,
+}
+
+#[derive(Default)]
+pub struct PluginGroupBuilder {
+    plugins: HashMap<TypeId, PluginEntry>,
+    order: Vec<TypeId>,
+}
+
+impl PluginGroupBuilder {
+    pub fn add<P: Plugin>(&mut self, plugin: P) -> &mut Self {
+        let type_id = TypeId::of::<P>();
+        let entry = PluginEntry {
+            plugin: Box::new(plugin),
+            enabled: true,
+        };
+        if self.plugins.insert(type_id, entry).is_some() {
+            panic!(
+                "Plugin '{}' was already added to the group",
+                type_name::<P>()
+            );
+        }
+        self.order.push(type_id);
+        self
+    }
+
+    pub fn enable<P: Plugin>(&mut self) -> &mut Self {
+        self.entry_mut::<P>().enabled = true;
+        self
+    }
+
+    pub fn disable<P: Plugin>(&mut self) -> &mut Self {
+        self.entry_mut::<P>().enabled = false;
+        self
+    }
+
+    /// Replace the `Target` plugin by `plugin`, keeping its place in the group.
+    pub fn replace<Target: Plugin, P: Plugin>(&mut self, plugin: P) -> &mut Self {
+        let target = TypeId::of::<Target>();
+        let type_id = TypeId::of::<P>();
+        let entry = self
+            .plugins
+            .remove(&target)
+            .unwrap_or_else(|| panic_missing_plugin(type_name::<Target>()));
+        if self.plugins.contains_key(&type_id) {
+            panic!(
+                "Plugin '{}' was already added to the group",
+                type_name::<P>()
+            );
+        }
+
+        self.plugins.insert(
+            type_id,
+            PluginEntry {
+                plugin: Box::new(plugin),
+                enabled: entry.enabled,
+            },
+        );
+        for id in self.order.iter_mut() {
+            if *id == target {
+                *id = type_id;
+            }
+        }
+        self
+    }
+
+    fn entry_mut<P: Plugin>(&mut self) -> &mut PluginEntry {
+        self.plugins
+            .get_mut(&TypeId::of::<P>())
+            .unwrap_or_else(|| panic_missing_plugin(type_name::<P>()))
+    }
+
+    /// Enabled plugins, ordered so that dependencies inside the group come first.
+    pub(crate) fn into_plugins(mut self) -> Vec<Box<dyn Plugin>> {
+        let mut plugins = Vec::new();
+        let mut names = HashMap::new();
+        for type_id in self.order.iter() {
+            let entry = self.plugins.remove(type_id).unwrap();
+            if entry.enabled {
+                names.insert(entry.plugin.name().to_string(), plugins.len());
+                plugins.push(Some(entry.plugin));
+            }
+        }
+
+        fn visit(
+            index: usize,
+            plugins: &mut Vec<Option<Box<dyn Plugin>>>,
+            names: &HashMap<String, usize>,
+            visiting: &mut Vec<usize>,
+            sorted: &mut Vec<Box<dyn Plugin>>,
+        ) {
+            if visiting.contains(&index) {
+                panic!("Plugin dependency cycle detected");
+            }
+            let dependencies = match &plugins[index] {
+                Some(plugin) => plugin.dependencies(),
+                None => return,
+            };
+
+            visiting.push(index);
+            for dependency in dependencies {
+                if let Some(&dependency) = names.get(dependency) {
+                    visit(dependency, plugins, names, visiting, sorted);
+                }
+            }
+            visiting.pop();
+
+            if let Some(plugin) = plugins[index].take() {
+                sorted.push(plugin);
+            }
+        }
+
+        let mut sorted = Vec::with_capacity(plugins.len());
+        for index in 0..plugins.len() {
+            visit(index, &mut plugins, &names, &mut Vec::new(), &mut sorted);
+        }
+        sorted
+    }
+}
+
+fn panic_missing_plugin(name: &str) -> ! {
+    panic!("Plugin '{}' is not part of the group", name)
+}
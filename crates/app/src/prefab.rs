@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use crate::{Component, Entity, World};
+
+/// Added to the entities spawned for the children of a prefab instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+/// Reusable entity template, instantiated by cloning its components.
+pub struct Prefab {
+    base: Option<Arc<Prefab>>,
+    template: World,
+    root: Entity,
+    children: Vec<Arc<Prefab>>,
+}
+
+impl Prefab {
+    pub fn builder() -> PrefabBuilder {
+        PrefabBuilder::new(None)
+    }
+
+    /// Start a prefab with every component and child of `base`, components
+    /// added to the new prefab override the base ones.
+    pub fn inherit(base: &Arc<Prefab>) -> PrefabBuilder {
+        PrefabBuilder::new(Some(base.clone()))
+    }
+
+    pub fn spawn(&self, world: &mut World) -> Entity {
+        let entity = world.spawn().entity();
+        self.write_to_entity(world, entity);
+        entity
+    }
+
+    /// Clone the prefab components onto `entity` and spawn its children.
+    pub fn write_to_entity(&self, world: &mut World, entity: Entity) {
+        if let Some(base) = &self.base {
+            base.write_to_entity(world, entity);
+        }
+        self.template.clone_entity_into(self.root, world, entity);
+        for child in self.children.iter() {
+            let child_entity = world.spawn().entity();
+            child.write_to_entity(world, child_entity);
+            world.add_component(child_entity, Parent(entity));
+        }
+    }
+}
+
+pub struct PrefabBuilder {
+    prefab: Prefab,
+}
+
+impl PrefabBuilder {
+    fn new(base: Option<Arc<Prefab>>) -> Self {
+        let mut template = World::default();
+        let root = template.spawn().entity();
+        PrefabBuilder {
+            prefab: Prefab {
+                base,
+                template,
+                root,
+                children: Vec::new(),
+            },
+        }
+    }
+
+    pub fn with_component<T: Component + Clone>(mut self, component: T) -> Self {
+        let prefab = &mut self.prefab;
+        prefab.template.register_clone::<T>();
+        prefab.template.add_component(prefab.root, component);
+        self
+    }
+
+    pub fn with_child(mut self, child: &Arc<Prefab>) -> Self {
+        self.prefab.children.push(child.clone());
+        self
+    }
+
+    pub fn build(self) -> Arc<Prefab> {
+        Arc::new(self.prefab)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CommandBuffer, Entities, Query};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Name(&'static str);
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(u32);
+    #[derive(Debug, Clone, PartialEq)]
+    struct Light(f32);
+
+    #[test]
+    fn prefab() {
+        let torch = Prefab::builder()
+            .with_component(Name("torch"))
+            .with_component(Light(1.))
+            .build();
+        let goblin = Prefab::builder()
+            .with_component(Name("goblin"))
+            .with_component(Health(10))
+            .build();
+        let chief = Prefab::inherit(&goblin)
+            .with_component(Health(30))
+            .with_child(&torch)
+            .build();
+
+        let mut world = World::default();
+        let mut commands = CommandBuffer::new();
+        let a = commands.spawn(&world).add_prefab(&goblin).entity();
+        let b = commands
+            .spawn(&world)
+            .add_prefab(&goblin)
+            .add(Health(5))
+            .entity();
+        let c = commands.spawn(&world).add_prefab(&chief).entity();
+        commands.flush(&mut world);
+
+        let query = Query::<(Entities, &Name, &Health)>::new();
+        assert_eq!(
+            query.iter(&world),
+            vec![
+                (a, &Name("goblin"), &Health(10)),
+                (b, &Name("goblin"), &Health(5)),
+                (c, &Name("goblin"), &Health(30)),
+            ]
+        );
+
+        let query = Query::<(&Name, &Light, &Parent)>::new();
+        assert_eq!(
+            query.iter(&world),
+            vec![(&Name("torch"), &Light(1.), &Parent(c))]
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{Access, ComponentTypeId, Entity, World};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynamicTerm {
+    pub type_id: ComponentTypeId,
+    pub write: bool,
+    /// Entities without the component still match, with a null pointer.
+    pub optional: bool,
+}
+
+/// Query built at runtime from component ids, for components that may not be
+/// Rust types.
+#[derive(Debug, Clone, Default)]
+pub struct DynamicQuery {
+    terms: Vec<DynamicTerm>,
+}
+
+/// Entity matched by a `DynamicQuery` with a pointer per term, in term order.
+#[derive(Debug, Clone)]
+pub struct DynamicItem {
+    pub entity: Entity,
+    pub components: Vec<*mut u8>,
+}
+
+impl DynamicQuery {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn term(mut self, term: DynamicTerm) -> Self {
+        self.terms.push(term);
+        self
+    }
+
+    pub fn read(self, type_id: ComponentTypeId) -> Self {
+        self.term(DynamicTerm {
+            type_id,
+            write: false,
+            optional: false,
+        })
+    }
+
+    pub fn write(self, type_id: ComponentTypeId) -> Self {
+        self.term(DynamicTerm {
+            type_id,
+            write: true,
+            optional: false,
+        })
+    }
+
+    pub fn read_optional(self, type_id: ComponentTypeId) -> Self {
+        self.term(DynamicTerm {
+            type_id,
+            write: false,
+            optional: true,
+        })
+    }
+
+    pub fn write_optional(self, type_id: ComponentTypeId) -> Self {
+        self.term(DynamicTerm {
+            type_id,
+            write: true,
+            optional: true,
+        })
+    }
+
+    pub fn terms(&self) -> &[DynamicTerm] {
+        &self.terms
+    }
+
+    pub fn access(&self, access: &mut Access<ComponentTypeId>) {
+        for term in self.terms.iter() {
+            if term.write {
+                access.add_write(term.type_id);
+            } else {
+                access.add_read(term.type_id);
+            }
+        }
+    }
+
+    /// Pointers stay valid until the world is structurally changed. Reading or
+    /// writing through them is only sound while nothing else borrows the same
+    /// components, as declared by `access`.
+    pub fn iter(&self, world: &World) -> Vec<DynamicItem> {
+        let components = world.components();
+        let mut bitset = world.entity_allocator().get_bitset().clone();
+        for term in self.terms.iter().filter(|term| !term.optional) {
+            match components.get_bitset_raw(&term.type_id) {
+                Some(other) => bitset.intersect_with(other),
+                None => bitset.clear(),
+            }
+        }
+
+        bitset
+            .into_iter()
+            .filter_map(|id| world.entity_allocator().get_entity(id as u32))
+            .map(|entity| DynamicItem {
+                entity,
+                components: self
+                    .terms
+                    .iter()
+                    .map(|term| {
+                        components
+                            .get_ptr_raw(&term.type_id, entity)
+                            .unwrap_or(std::ptr::null_mut())
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::Layout;
+
+    use crate::ComponentDescriptor;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Velocity(f32);
+
+    #[test]
+    fn dynamic_query() {
+        let mut world = World::default();
+        let position = world.register_dynamic_component(ComponentDescriptor::new(
+            "position",
+            Layout::new::<[f32; 2]>(),
+            None,
+        ));
+        let tag = world.register_dynamic_component(ComponentDescriptor::new(
+            "tag",
+            Layout::new::<()>(),
+            None,
+        ));
+        assert!(position.is_dynamic() && position != tag);
+        assert_eq!(world.dynamic_component_id("position"), Some(position));
+        assert_eq!(
+            world.register_dynamic_component(ComponentDescriptor::new(
+                "position",
+                Layout::new::<[f32; 2]>(),
+                None,
+            )),
+            position
+        );
+
+        let a = world.spawn().add(Velocity(1.)).entity();
+        let b = world.spawn().add(Velocity(2.)).entity();
+        world.spawn().add(Velocity(3.));
+        unsafe {
+            let mut value = [0f32, 0.];
+            world.add_component_raw(a, position, value.as_mut_ptr().cast());
+            value = [10., 10.];
+            world.add_component_raw(b, position, value.as_mut_ptr().cast());
+            world.add_component_raw(b, tag, std::ptr::NonNull::<()>::dangling().as_ptr().cast());
+        }
+
+        let query = DynamicQuery::new()
+            .write(position)
+            .read(ComponentTypeId::of::<Velocity>())
+            .read_optional(tag);
+        let items = query.iter(&world);
+        assert_eq!(items.len(), 2);
+        for item in items.iter() {
+            unsafe {
+                let position = &mut *item.components[0].cast::<[f32; 2]>();
+                let velocity = &*item.components[1].cast::<Velocity>();
+                position[0] += velocity.0;
+            }
+        }
+        assert!(items[0].components[2].is_null());
+        assert!(!items[1].components[2].is_null());
+
+        unsafe {
+            let ptr = world.component_ptr(&position, b).unwrap();
+            assert_eq!(*ptr.cast::<[f32; 2]>(), [12., 10.]);
+        }
+
+        world.remove_component_raw(a, position);
+        assert_eq!(query.iter(&world).len(), 1);
+
+        let mut access = Access::default();
+        query.access(&mut access);
+        assert_eq!(access.writes().collect::<Vec<_>>(), vec![&position]);
+    }
+}
@@ -1,5 +1,7 @@
+mod dynamic;
 mod query;
 mod view;
 
+pub use dynamic::*;
 pub use query::*;
 pub use view::*;
@@ -1,6 +1,6 @@
 use std::marker::PhantomData;
 
-use crate::World;
+use crate::{Access, ComponentTypeId, World};
 
 use super::{IntoView, View};
 
@@ -27,14 +27,20 @@ impl<V: IntoView> Query<V> {
     }
 }
 
-pub trait QuerySet: Send + Sync {}
+pub trait QuerySet: Send + Sync {
+    fn access(access: &mut Access<ComponentTypeId>);
+}
 
 macro_rules! impl_queryset_tuple {
     ($($name: ident),*) => {
         impl<$($name,)*> QuerySet for ($($name,)*)
         where
             $($name: QuerySet,)*
-        {}
+        {
+            fn access(access: &mut Access<ComponentTypeId>) {
+                $($name::access(access);)*
+            }
+        }
     };
 }
 
@@ -50,8 +56,18 @@ macro_rules! queryset_tuple {
 
 queryset_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z);
 
-impl QuerySet for () {}
-impl<V> QuerySet for Query<V> where V: IntoView + Send + Sync {}
+impl QuerySet for () {
+    fn access(_access: &mut Access<ComponentTypeId>) {}
+}
+
+impl<V> QuerySet for Query<V>
+where
+    V: IntoView + Send + Sync,
+{
+    fn access(access: &mut Access<ComponentTypeId>) {
+        <V::View as View>::access(access)
+    }
+}
 
 #[cfg(test)]
 mod tests {
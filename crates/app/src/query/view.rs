@@ -2,7 +2,7 @@ use util::bit_set::BitSet;
 
 use crate::{
     accessor::{Read, TryRead, TryWrite, Write},
-    Component, Components, Entity,
+    Access, Component, ComponentTypeId, Components, Entity,
 };
 
 pub trait IntoView {
@@ -14,6 +14,7 @@ pub trait View<'a>: Sized {
 
     fn filter(bitset: &mut BitSet, components: &Components);
     fn fetch(entity: Entity, components: &Components) -> Self::Item;
+    fn access(access: &mut Access<ComponentTypeId>);
 }
 
 impl<'a, T: Component> IntoView for &'a T {
@@ -49,6 +50,7 @@ impl<'a> View<'a> for Entities {
     fn fetch(entity: Entity, _components: &Components) -> Self::Item {
         entity
     }
+    fn access(_access: &mut Access<ComponentTypeId>) {}
 }
 
 macro_rules! view_tuple {
@@ -62,6 +64,9 @@ macro_rules! view_tuple {
             fn fetch(entity: Entity, components: &Components) -> Self::Item {
                 ($($name::fetch(entity, components),)*)
             }
+            fn access(access: &mut Access<ComponentTypeId>) {
+                $($name::access(access);)*
+            }
         }
 
         impl<$($name: IntoView),*> IntoView for ($($name,)*) {
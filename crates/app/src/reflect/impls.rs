@@ -0,0 +1,265 @@
+use std::{any::Any, convert::TryFrom, fmt};
+
+use crate::{Entity, FieldInfo, Reflect, ReflectError, TypeInfo, Value};
+
+macro_rules! impl_reflect_common {
+    () => {
+        fn type_name(&self) -> &'static str {
+            std::any::type_name::<Self>()
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn as_reflect(&self) -> &dyn Reflect {
+            self
+        }
+
+        fn as_reflect_mut(&mut self) -> &mut dyn Reflect {
+            self
+        }
+    };
+}
+
+macro_rules! impl_reflect_value {
+    ($ty:ty, $variant:ident, $stored:ty) => {
+        impl_reflect_value!($ty, $variant, |value| <$stored>::from(value));
+    };
+    ($ty:ty, $variant:ident, |$value:ident| $convert:expr) => {
+        impl Reflect for $ty {
+            impl_reflect_common!();
+
+            fn type_info() -> TypeInfo {
+                TypeInfo {
+                    type_name: std::any::type_name::<Self>(),
+                    fields: Vec::new(),
+                }
+            }
+
+            fn to_value(&self) -> Value {
+                let $value = self.clone();
+                Value::$variant($convert)
+            }
+
+            fn from_value(value: &Value) -> Result<Self, ReflectError> {
+                cast(value)
+            }
+
+            fn apply(&mut self, value: &Value) -> Result<(), ReflectError> {
+                *self = cast(value)?;
+                Ok(())
+            }
+
+            fn debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(self, f)
+            }
+        }
+    };
+}
+
+trait Cast: Sized {
+    fn cast(value: &Value) -> Option<Self>;
+}
+
+fn cast<T: Cast>(value: &Value) -> Result<T, ReflectError> {
+    T::cast(value).ok_or_else(|| ReflectError::mismatch::<T>(value))
+}
+
+macro_rules! impl_cast_int {
+    ($($ty:ty),*) => {
+        $(
+            impl Cast for $ty {
+                fn cast(value: &Value) -> Option<Self> {
+                    match value {
+                        Value::Int(value) => <$ty>::try_from(*value).ok(),
+                        Value::UInt(value) => <$ty>::try_from(*value).ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+impl_cast_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl Cast for f32 {
+    fn cast(value: &Value) -> Option<Self> {
+        f64::cast(value).map(|value| value as f32)
+    }
+}
+
+impl Cast for f64 {
+    fn cast(value: &Value) -> Option<Self> {
+        match value {
+            Value::Float(value) => Some(*value),
+            Value::Int(value) => Some(*value as f64),
+            Value::UInt(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+}
+
+impl Cast for bool {
+    fn cast(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl Cast for String {
+    fn cast(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl Cast for Entity {
+    fn cast(value: &Value) -> Option<Self> {
+        match value {
+            Value::Entity(value) => Some(*value),
+            // Entities are read back from scenes as plain structs.
+            value => Some(Entity::new(
+                u32::cast(value.field("id")?)?,
+                u32::cast(value.field("generation")?)?,
+            )),
+        }
+    }
+}
+
+impl_reflect_value!(i8, Int, i64);
+impl_reflect_value!(i16, Int, i64);
+impl_reflect_value!(i32, Int, i64);
+impl_reflect_value!(i64, Int, i64);
+impl_reflect_value!(isize, Int, |value| value as i64);
+impl_reflect_value!(u8, UInt, u64);
+impl_reflect_value!(u16, UInt, u64);
+impl_reflect_value!(u32, UInt, u64);
+impl_reflect_value!(u64, UInt, u64);
+impl_reflect_value!(usize, UInt, |value| value as u64);
+impl_reflect_value!(f32, Float, f64);
+impl_reflect_value!(f64, Float, f64);
+impl_reflect_value!(bool, Bool, bool);
+impl_reflect_value!(String, String, String);
+impl_reflect_value!(Entity, Entity, Entity);
+
+impl<T: Reflect> Reflect for Option<T> {
+    impl_reflect_common!();
+
+    fn type_info() -> TypeInfo {
+        TypeInfo {
+            type_name: std::any::type_name::<Self>(),
+            fields: vec![FieldInfo::of::<T>("0")],
+        }
+    }
+
+    fn fields(&self) -> Vec<(&'static str, &dyn Reflect)> {
+        self.iter().map(|value| ("0", value.as_reflect())).collect()
+    }
+
+    fn field(&self, name: &str) -> Option<&dyn Reflect> {
+        match name {
+            "0" => self.as_ref().map(|value| value.as_reflect()),
+            _ => None,
+        }
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+        match name {
+            "0" => self.as_mut().map(|value| value.as_reflect_mut()),
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Some(value) => value.to_value(),
+            None => Value::Unit,
+        }
+    }
+
+    fn from_value(value: &Value) -> Result<Self, ReflectError> {
+        match value {
+            Value::Unit => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+
+    fn apply(&mut self, value: &Value) -> Result<(), ReflectError> {
+        match (self.as_mut(), value) {
+            (_, Value::Unit) => *self = None,
+            (Some(inner), value) => inner.apply(value)?,
+            (None, value) => *self = Some(T::from_value(value)?),
+        }
+        Ok(())
+    }
+
+    fn debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Some(value) => f.debug_tuple("Some").field(&value.as_reflect()).finish(),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+impl<T: Reflect> Reflect for Vec<T> {
+    impl_reflect_common!();
+
+    fn type_info() -> TypeInfo {
+        TypeInfo {
+            type_name: std::any::type_name::<Self>(),
+            fields: Vec::new(),
+        }
+    }
+
+    fn field(&self, name: &str) -> Option<&dyn Reflect> {
+        let index = name.parse::<usize>().ok()?;
+        self.get(index).map(|value| value.as_reflect())
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+        let index = name.parse::<usize>().ok()?;
+        self.get_mut(index).map(|value| value.as_reflect_mut())
+    }
+
+    fn to_value(&self) -> Value {
+        Value::List(self.iter().map(|value| value.to_value()).collect())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, ReflectError> {
+        match value {
+            Value::List(values) => values.iter().map(T::from_value).collect(),
+            _ => Err(ReflectError::mismatch::<Self>(value)),
+        }
+    }
+
+    fn apply(&mut self, value: &Value) -> Result<(), ReflectError> {
+        match value {
+            Value::List(values) => {
+                self.truncate(values.len());
+                for (index, value) in values.iter().enumerate() {
+                    match self.get_mut(index) {
+                        Some(item) => item.apply(value)?,
+                        None => self.push(T::from_value(value)?),
+                    }
+                }
+                Ok(())
+            }
+            _ => Err(ReflectError::mismatch::<Self>(value)),
+        }
+    }
+
+    fn debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|value| value.as_reflect()))
+            .finish()
+    }
+}
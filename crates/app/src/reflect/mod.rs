@@ -0,0 +1,321 @@
+mod impls;
+pub mod registry;
+pub mod value;
+
+use std::{
+    any::{type_name, Any},
+    fmt,
+};
+
+pub use macros::Reflect;
+pub use registry::*;
+pub use value::*;
+
+#[derive(Debug, Clone)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    pub type_name: &'static str,
+}
+
+impl FieldInfo {
+    pub fn of<T>(name: &'static str) -> Self {
+        FieldInfo {
+            name,
+            type_name: type_name::<T>(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeInfo {
+    pub type_name: &'static str,
+    pub fields: Vec<FieldInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReflectError {
+    Mismatch {
+        type_name: &'static str,
+        value: Value,
+    },
+    UnknownField {
+        type_name: &'static str,
+        field: String,
+    },
+    MissingField {
+        type_name: &'static str,
+        field: &'static str,
+    },
+    InvalidPath(String),
+    Unregistered(String),
+}
+
+impl ReflectError {
+    pub fn mismatch<T: ?Sized>(value: &Value) -> Self {
+        ReflectError::Mismatch {
+            type_name: type_name::<T>(),
+            value: value.clone(),
+        }
+    }
+
+    pub fn missing_field<T: ?Sized>(field: &'static str) -> Self {
+        ReflectError::MissingField {
+            type_name: type_name::<T>(),
+            field,
+        }
+    }
+}
+
+impl fmt::Display for ReflectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReflectError::Mismatch { type_name, value } => {
+                write!(f, "cannot apply {:?} to {}", value, type_name)
+            }
+            ReflectError::UnknownField { type_name, field } => {
+                write!(f, "{} has no field '{}'", type_name, field)
+            }
+            ReflectError::MissingField { type_name, field } => {
+                write!(f, "{} is missing field '{}'", type_name, field)
+            }
+            ReflectError::InvalidPath(path) => write!(f, "invalid path '{}'", path),
+            ReflectError::Unregistered(name) => write!(f, "type '{}' is not registered", name),
+        }
+    }
+}
+
+impl std::error::Error for ReflectError {}
+
+/// Runtime access to the fields of a type, implemented with `#[derive(Reflect)]`.
+pub trait Reflect: Any + Send + Sync {
+    fn type_name(&self) -> &'static str;
+
+    fn type_info() -> TypeInfo
+    where
+        Self: Sized;
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn as_reflect(&self) -> &dyn Reflect;
+    fn as_reflect_mut(&mut self) -> &mut dyn Reflect;
+
+    fn fields(&self) -> Vec<(&'static str, &dyn Reflect)> {
+        Vec::new()
+    }
+
+    fn field(&self, _name: &str) -> Option<&dyn Reflect> {
+        None
+    }
+
+    fn field_mut(&mut self, _name: &str) -> Option<&mut dyn Reflect> {
+        None
+    }
+
+    fn to_value(&self) -> Value;
+
+    /// Build a new instance from `value`, every field has to be present. Used
+    /// to fill empty containers such as `None`.
+    fn from_value(value: &Value) -> Result<Self, ReflectError>
+    where
+        Self: Sized;
+
+    fn apply(&mut self, value: &Value) -> Result<(), ReflectError>;
+
+    fn debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+impl fmt::Debug for dyn Reflect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.debug(f)
+    }
+}
+
+impl dyn Reflect {
+    pub fn downcast_ref<T: Reflect>(&self) -> Option<&T> {
+        self.as_any().downcast_ref::<T>()
+    }
+
+    pub fn downcast_mut<T: Reflect>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut::<T>()
+    }
+
+    /// Get a nested field by a dot separated path such as `position.x`.
+    pub fn path(&self, path: &str) -> Option<&dyn Reflect> {
+        path.split('.')
+            .filter(|name| !name.is_empty())
+            .try_fold(self, |value, name| value.field(name))
+    }
+
+    pub fn path_mut(&mut self, path: &str) -> Option<&mut dyn Reflect> {
+        path.split('.')
+            .filter(|name| !name.is_empty())
+            .try_fold(self, |value, name| value.field_mut(name))
+    }
+
+    pub fn set_path(&mut self, path: &str, value: &Value) -> Result<(), ReflectError> {
+        self.path_mut(path)
+            .ok_or_else(|| ReflectError::InvalidPath(path.to_string()))?
+            .apply(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ComponentTypeId, Entity, World};
+
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Reflect)]
+    #[reflect(crate = "crate")]
+    struct Vec2 {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Debug, Default, Reflect)]
+    #[reflect(crate = "crate")]
+    struct Transform {
+        position: Vec2,
+        scale: f32,
+        #[reflect(ignore)]
+        _dirty: bool,
+    }
+
+    #[derive(Debug, Default, Reflect)]
+    #[reflect(crate = "crate")]
+    struct Target(Option<u32>, Vec<u32>);
+
+    #[derive(Debug, Default, PartialEq, Reflect)]
+    #[reflect(crate = "crate")]
+    struct Labeled<T> {
+        label: String,
+        value: T,
+    }
+
+    #[test]
+    fn reflect_generic() {
+        let mut labeled = Labeled::<Vec2>::default();
+        labeled
+            .as_reflect_mut()
+            .set_path("value.y", &Value::Float(4.))
+            .unwrap();
+        assert_eq!(labeled.value, Vec2 { x: 0., y: 4. });
+        assert_eq!(
+            Labeled::<Vec2>::type_info().type_name,
+            std::any::type_name::<Labeled<Vec2>>()
+        );
+    }
+
+    #[test]
+    fn reflect_fields() {
+        let mut transform = Transform::default();
+        let reflect = transform.as_reflect_mut();
+        reflect.set_path("position.x", &Value::Float(2.)).unwrap();
+        reflect.set_path("scale", &Value::Int(3)).unwrap();
+        assert!(reflect.set_path("_dirty", &Value::Bool(true)).is_err());
+        assert!(reflect.set_path("scale", &Value::Bool(true)).is_err());
+
+        assert_eq!(transform.position, Vec2 { x: 2., y: 0. });
+        assert_eq!(transform.scale, 3.);
+        assert_eq!(
+            Transform::type_info()
+                .fields
+                .iter()
+                .map(|field| field.name)
+                .collect::<Vec<_>>(),
+            vec!["position", "scale"]
+        );
+        assert_eq!(
+            transform.to_value().field("position"),
+            Some(&Value::Struct(vec![
+                ("x".to_string(), Value::Float(2.)),
+                ("y".to_string(), Value::Float(0.))
+            ]))
+        );
+
+        let mut target = Target::default();
+        target
+            .apply(&Value::Struct(vec![
+                ("0".to_string(), Value::UInt(7)),
+                (
+                    "1".to_string(),
+                    Value::List(vec![Value::UInt(4), Value::Int(5)]),
+                ),
+            ]))
+            .unwrap();
+        assert_eq!(target.0, Some(7));
+        assert_eq!(target.1, vec![4, 5]);
+        assert_eq!(
+            target
+                .as_reflect()
+                .path("1.1")
+                .and_then(|value| value.downcast_ref::<u32>()),
+            Some(&5)
+        );
+    }
+
+    #[test]
+    fn reflect_option() {
+        let mut position = None::<Vec2>;
+        position
+            .apply(&Value::Struct(vec![
+                ("x".to_string(), Value::Float(1.)),
+                ("y".to_string(), Value::Int(2)),
+            ]))
+            .unwrap();
+        assert_eq!(position, Some(Vec2 { x: 1., y: 2. }));
+        position.apply(&Value::Unit).unwrap();
+        assert_eq!(position, None);
+        assert_eq!(
+            Option::<Vec2>::from_value(&Value::Struct(vec![("x".to_string(), Value::Float(1.))])),
+            Err(ReflectError::MissingField {
+                type_name: std::any::type_name::<Vec2>(),
+                field: "y",
+            })
+        );
+
+        let mut target = None::<Entity>;
+        target
+            .apply(&Value::Struct(vec![
+                ("id".to_string(), Value::UInt(3)),
+                ("generation".to_string(), Value::UInt(1)),
+            ]))
+            .unwrap();
+        assert_eq!(target, Some(Entity::new(3, 1)));
+    }
+
+    #[test]
+    fn type_registry() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Transform>().register::<Target>();
+
+        let mut world = World::default();
+        let entity = world
+            .spawn()
+            .add(Transform::default())
+            .add(Target(None, vec![1]))
+            .add(0u8)
+            .entity();
+
+        let type_id = ComponentTypeId::of::<Transform>();
+        let ptr = world.component_ptr(&type_id, entity).unwrap();
+        unsafe {
+            registry
+                .set_path(&type_id, ptr, "position.y", &Value::Float(5.))
+                .unwrap();
+            let y = registry.get_path(&type_id, ptr, "position.y").unwrap();
+            assert_eq!(y.downcast_ref::<f32>(), Some(&5.));
+        }
+
+        assert_eq!(
+            registry.get_by_name("Transform").map(|r| r.type_id()),
+            Some(type_id)
+        );
+        assert_eq!(registry.entity_components(&world, entity).len(), 2);
+        assert_eq!(
+            format!("{:?}", registry.debug_entity(&world, entity)),
+            "0v0 { Target: Target { 0: None, 1: [1] }, \
+             Transform: Transform { position: Vec2 { x: 0.0, y: 5.0 }, scale: 0.0 } }"
+        );
+    }
+}
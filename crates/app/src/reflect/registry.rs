@@ -0,0 +1,226 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{Component, ComponentTypeId, Entity, Reflect, ReflectError, TypeInfo, Value, World};
+
+unsafe fn reflect_ptr<T: Reflect>(ptr: *mut u8) -> *mut dyn Reflect {
+    ptr.cast::<T>() as *mut dyn Reflect
+}
+
+fn insert_value<T: Component + Reflect + Default>(
+    world: &mut World,
+    entity: Entity,
+    value: &Value,
+) -> Result<(), ReflectError> {
+    let mut component = T::default();
+    component.apply(value)?;
+    world.add_component(entity, component);
+    Ok(())
+}
+
+fn typed_value<T: Reflect + Default>(value: &Value) -> Result<Value, ReflectError> {
+    let mut component = T::default();
+    component.apply(value)?;
+    Ok(component.to_value())
+}
+
+#[derive(Clone)]
+pub struct ComponentRegistration {
+    type_id: ComponentTypeId,
+    type_info: TypeInfo,
+    short_name: &'static str,
+    reflect: unsafe fn(*mut u8) -> *mut dyn Reflect,
+    insert: fn(&mut World, Entity, &Value) -> Result<(), ReflectError>,
+    typed_value: fn(&Value) -> Result<Value, ReflectError>,
+}
+
+impl ComponentRegistration {
+    pub fn of<T: Component + Reflect + Default>() -> Self {
+        let type_info = T::type_info();
+        let short_name = short_name(type_info.type_name);
+        ComponentRegistration {
+            type_id: ComponentTypeId::of::<T>(),
+            type_info,
+            short_name,
+            reflect: reflect_ptr::<T>,
+            insert: insert_value::<T>,
+            typed_value: typed_value::<T>,
+        }
+    }
+
+    #[inline]
+    pub fn type_id(&self) -> ComponentTypeId {
+        self.type_id
+    }
+
+    #[inline]
+    pub fn type_info(&self) -> &TypeInfo {
+        &self.type_info
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.type_info.type_name
+    }
+
+    /// Type name without its module path.
+    #[inline]
+    pub fn short_name(&self) -> &'static str {
+        self.short_name
+    }
+
+    /// # Safety
+    /// `ptr` must point to a live component of the registered type.
+    pub unsafe fn reflect<'a>(&self, ptr: *mut u8) -> &'a dyn Reflect {
+        &*(self.reflect)(ptr)
+    }
+
+    /// # Safety
+    /// `ptr` must point to a live component of the registered type, not
+    /// borrowed anywhere else.
+    pub unsafe fn reflect_mut<'a>(&self, ptr: *mut u8) -> &'a mut dyn Reflect {
+        &mut *(self.reflect)(ptr)
+    }
+
+    /// Add a component built from its default with `value` applied on top.
+    pub fn insert(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        value: &Value,
+    ) -> Result<(), ReflectError> {
+        (self.insert)(world, entity, value)
+    }
+
+    /// `value` as the component would produce it: missing fields take their
+    /// default and untyped data, such as entities read back as plain structs,
+    /// gets its kind back.
+    pub fn typed_value(&self, value: &Value) -> Result<Value, ReflectError> {
+        (self.typed_value)(value)
+    }
+}
+
+fn short_name(type_name: &'static str) -> &'static str {
+    let path = type_name.split('<').next().unwrap_or(type_name);
+    match path.rfind("::") {
+        Some(index) => &type_name[index + 2..],
+        None => type_name,
+    }
+}
+
+/// Reflection data of the registered component types.
+#[derive(Default)]
+pub struct TypeRegistry {
+    registrations: HashMap<ComponentTypeId, ComponentRegistration>,
+    names: HashMap<&'static str, ComponentTypeId>,
+}
+
+impl TypeRegistry {
+    pub fn register<T: Component + Reflect + Default>(&mut self) -> &mut Self {
+        let registration = ComponentRegistration::of::<T>();
+        self.names
+            .insert(registration.name(), registration.type_id());
+        self.names
+            .entry(registration.short_name())
+            .or_insert_with(|| registration.type_id());
+        self.registrations
+            .insert(registration.type_id(), registration);
+        self
+    }
+
+    pub fn get(&self, type_id: &ComponentTypeId) -> Option<&ComponentRegistration> {
+        self.registrations.get(type_id)
+    }
+
+    /// Find a registration by full or short type name.
+    pub fn get_by_name(&self, name: &str) -> Option<&ComponentRegistration> {
+        self.names
+            .get(name)
+            .and_then(|type_id| self.registrations.get(type_id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ComponentRegistration> {
+        self.registrations.values()
+    }
+
+    /// # Safety
+    /// `ptr` must point to a live component of type `type_id`, such as one
+    /// returned by `BlobSparseSet::get_ptr`.
+    pub unsafe fn get_path<'a>(
+        &self,
+        type_id: &ComponentTypeId,
+        ptr: *mut u8,
+        path: &str,
+    ) -> Result<&'a dyn Reflect, ReflectError> {
+        self.registration(type_id)?
+            .reflect(ptr)
+            .path(path)
+            .ok_or_else(|| ReflectError::InvalidPath(path.to_string()))
+    }
+
+    /// # Safety
+    /// `ptr` must point to a live component of type `type_id`, not borrowed
+    /// anywhere else.
+    pub unsafe fn set_path(
+        &self,
+        type_id: &ComponentTypeId,
+        ptr: *mut u8,
+        path: &str,
+        value: &Value,
+    ) -> Result<(), ReflectError> {
+        self.registration(type_id)?
+            .reflect_mut(ptr)
+            .set_path(path, value)
+    }
+
+    fn registration(
+        &self,
+        type_id: &ComponentTypeId,
+    ) -> Result<&ComponentRegistration, ReflectError> {
+        self.get(type_id)
+            .ok_or_else(|| ReflectError::Unregistered(type_id.to_string()))
+    }
+
+    /// Reflected components of an entity, unregistered ones are skipped.
+    pub fn entity_components<'a>(
+        &self,
+        world: &'a World,
+        entity: Entity,
+    ) -> Vec<(&ComponentRegistration, &'a dyn Reflect)> {
+        let mut components = world
+            .entity_component_types(entity)
+            .into_iter()
+            .filter_map(|type_id| {
+                let registration = self.get(&type_id)?;
+                let ptr = world.component_ptr(&type_id, entity)?;
+                Some((registration, unsafe { registration.reflect(ptr) }))
+            })
+            .collect::<Vec<_>>();
+        components.sort_by_key(|(registration, _)| registration.name());
+        components
+    }
+
+    /// `Debug` view of every registered component of an entity.
+    pub fn debug_entity<'a>(&'a self, world: &'a World, entity: Entity) -> EntityDebug<'a> {
+        EntityDebug {
+            registry: self,
+            world,
+            entity,
+        }
+    }
+}
+
+pub struct EntityDebug<'a> {
+    registry: &'a TypeRegistry,
+    world: &'a World,
+    entity: Entity,
+}
+
+impl<'a> fmt::Debug for EntityDebug<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct(&format!("{:?}", self.entity));
+        for (registration, component) in self.registry.entity_components(self.world, self.entity) {
+            debug.field(registration.short_name(), &component);
+        }
+        debug.finish()
+    }
+}
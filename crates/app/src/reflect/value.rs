@@ -0,0 +1,225 @@
+use std::{borrow::Cow, collections::BTreeSet, fmt, sync::Mutex};
+
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeStruct, SerializeTuple},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::Entity;
+
+/// Type erased data of a reflected value.
+///
+/// Serialized as plain data: structs are written with their field names, such
+/// as `(x: 1.0, y: 2.0)` in RON, tuple structs as tuples and entities as
+/// `(id: 1, generation: 0)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    Entity(Entity),
+    List(Vec<Value>),
+    Struct(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Field of a struct value, list items are named by their index.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value),
+            Value::List(values) => name
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| values.get(index)),
+            _ => None,
+        }
+    }
+
+    /// Fields of a struct value, list items are named by their index as tuple
+    /// structs are read back as lists.
+    pub fn fields(&self) -> Vec<(Cow<'_, str>, &Value)> {
+        match self {
+            Value::Struct(fields) => fields
+                .iter()
+                .map(|(name, value)| (Cow::Borrowed(name.as_str()), value))
+                .collect(),
+            Value::List(values) => values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| (Cow::Owned(index.to_string()), value))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Replace every entity referenced by the value, stopping at the first error.
+    pub fn map_entities<E, F>(&mut self, map: &mut F) -> Result<(), E>
+    where
+        F: FnMut(Entity) -> Result<Entity, E>,
+    {
+        match self {
+            Value::Entity(entity) => *entity = map(*entity)?,
+            Value::List(values) => {
+                for value in values.iter_mut() {
+                    value.map_entities(map)?;
+                }
+            }
+            Value::Struct(fields) => {
+                for (_, value) in fields.iter_mut() {
+                    value.map_entities(map)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn is_tuple(fields: &[(String, Value)]) -> bool {
+    !fields.is_empty()
+        && fields
+            .iter()
+            .enumerate()
+            .all(|(index, (name, _))| *name == index.to_string())
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Serde wants struct field names to be `'static`, each distinct name is
+/// leaked once.
+fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut names = NAMES.lock().unwrap();
+    match names.get(name) {
+        Some(&name) => name,
+        None => {
+            let name: &'static str = Box::leak(name.into());
+            names.insert(name);
+            name
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Unit => serializer.serialize_unit(),
+            Value::Bool(value) => serializer.serialize_bool(*value),
+            Value::Int(value) => serializer.serialize_i64(*value),
+            Value::UInt(value) => serializer.serialize_u64(*value),
+            Value::Float(value) => serializer.serialize_f64(*value),
+            Value::String(value) => serializer.serialize_str(value),
+            Value::Entity(entity) => entity.serialize(serializer),
+            Value::List(values) => serializer.collect_seq(values),
+            Value::Struct(fields) if is_tuple(fields) => {
+                let mut tuple = serializer.serialize_tuple(fields.len())?;
+                for (_, value) in fields.iter() {
+                    tuple.serialize_element(value)?;
+                }
+                tuple.end()
+            }
+            Value::Struct(fields) if fields.iter().all(|(name, _)| is_identifier(name)) => {
+                let mut state = serializer.serialize_struct("", fields.len())?;
+                for (name, value) in fields.iter() {
+                    state.serialize_field(intern(name), value)?;
+                }
+                state.end()
+            }
+            Value::Struct(fields) => {
+                serializer.collect_map(fields.iter().map(|(name, value)| (name, value)))
+            }
+        }
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a reflected value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Unit)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Unit)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Int(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::UInt(value))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Value::Float(value))
+    }
+
+    fn visit_char<E>(self, value: char) -> Result<Value, E> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Value::List(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut fields = Vec::new();
+        // Keys go through `Value` as RON only reads struct field names with
+        // `deserialize_any`.
+        while let Some((name, value)) = map.next_entry()? {
+            match name {
+                Value::String(name) => fields.push((name, value)),
+                name => {
+                    return Err(de::Error::custom(format!(
+                        "expected a field name, found {:?}",
+                        name
+                    )))
+                }
+            }
+        }
+        Ok(Value::Struct(fields))
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
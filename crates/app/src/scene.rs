@@ -0,0 +1,254 @@
+use std::{collections::BTreeMap, collections::HashMap, fmt};
+
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{Entity, ReflectError, TypeRegistry, Value, World};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Ron(ron::Error),
+    Reflect(ReflectError),
+    UnknownComponent(String),
+    /// A component references an entity that is not part of the scene.
+    UnknownEntity(Entity),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Ron(err) => write!(f, "{}", err),
+            SceneError::Reflect(err) => write!(f, "{}", err),
+            SceneError::UnknownComponent(name) => {
+                write!(f, "component '{}' is not registered", name)
+            }
+            SceneError::UnknownEntity(entity) => {
+                write!(f, "entity {:?} is not part of the scene", entity)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<ron::Error> for SceneError {
+    fn from(err: ron::Error) -> Self {
+        SceneError::Ron(err)
+    }
+}
+
+impl From<ReflectError> for SceneError {
+    fn from(err: ReflectError) -> Self {
+        SceneError::Reflect(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DynamicEntity {
+    pub entity: Entity,
+    /// Component values keyed by their registered type name.
+    pub components: BTreeMap<String, Value>,
+}
+
+/// Registered components of a set of entities, as data that can be saved to
+/// and loaded from RON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DynamicScene {
+    pub entities: Vec<DynamicEntity>,
+}
+
+impl DynamicScene {
+    /// Capture every live entity of the world, components missing from the
+    /// registry are skipped.
+    pub fn from_world(world: &World, registry: &TypeRegistry) -> Self {
+        let entities = world
+            .entities()
+            .into_iter()
+            .map(|entity| DynamicEntity {
+                entity,
+                components: registry
+                    .entity_components(world, entity)
+                    .into_iter()
+                    .map(|(registration, component)| {
+                        // Prefer the short name when it is unambiguous, it is easier to edit.
+                        let name = match registry.get_by_name(registration.short_name()) {
+                            Some(found) if found.type_id() == registration.type_id() => {
+                                registration.short_name()
+                            }
+                            _ => registration.name(),
+                        };
+                        (name.to_string(), component.to_value())
+                    })
+                    .collect(),
+            })
+            .collect();
+        DynamicScene { entities }
+    }
+
+    /// Spawn the scene entities into `world`. Entity references are remapped to
+    /// the newly allocated entities, the mapping is returned. On error nothing
+    /// is left in the world.
+    pub fn write_to_world(
+        &self,
+        world: &mut World,
+        registry: &TypeRegistry,
+    ) -> Result<HashMap<Entity, Entity>, SceneError> {
+        let entity_map = self
+            .entities
+            .iter()
+            .map(|scene_entity| (scene_entity.entity, world.spawn().entity()))
+            .collect::<HashMap<_, _>>();
+
+        match self.insert_components(world, registry, &entity_map) {
+            Ok(()) => Ok(entity_map),
+            Err(err) => {
+                entity_map
+                    .values()
+                    .for_each(|&entity| world.despawn(entity));
+                Err(err)
+            }
+        }
+    }
+
+    fn insert_components(
+        &self,
+        world: &mut World,
+        registry: &TypeRegistry,
+        entity_map: &HashMap<Entity, Entity>,
+    ) -> Result<(), SceneError> {
+        for scene_entity in self.entities.iter() {
+            let entity = entity_map[&scene_entity.entity];
+            for (name, value) in scene_entity.components.iter() {
+                let registration = registry
+                    .get_by_name(name)
+                    .ok_or_else(|| SceneError::UnknownComponent(name.clone()))?;
+                let mut value = registration.typed_value(value)?;
+                value.map_entities(&mut |entity| {
+                    entity_map
+                        .get(&entity)
+                        .copied()
+                        .ok_or(SceneError::UnknownEntity(entity))
+                })?;
+                registration.insert(world, entity, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_ron(&self) -> Result<String, SceneError> {
+        let config = PrettyConfig::new().with_decimal_floats(true);
+        Ok(ron::ser::to_string_pretty(self, config)?)
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self, SceneError> {
+        Ok(ron::from_str(ron)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Query, Reflect};
+
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Reflect)]
+    #[reflect(crate = "crate")]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Debug, Default, PartialEq, Reflect)]
+    #[reflect(crate = "crate")]
+    struct Name(String);
+
+    #[derive(Debug, Default, PartialEq, Reflect)]
+    #[reflect(crate = "crate")]
+    struct Follow {
+        target: Option<Entity>,
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut registry = TypeRegistry::default();
+        registry
+            .register::<Position>()
+            .register::<Name>()
+            .register::<Follow>();
+
+        let mut world = World::default();
+        let leader = world
+            .spawn()
+            .add(Name("leader".to_string()))
+            .add(Position { x: 1., y: 2. })
+            .entity();
+        world
+            .spawn()
+            .add(Name("follower".to_string()))
+            .add(Follow {
+                target: Some(leader),
+            })
+            .add(0u8);
+
+        let ron = DynamicScene::from_world(&world, &registry)
+            .to_ron()
+            .unwrap();
+        assert!(ron.contains(r#""Name": ("leader")"#));
+        assert!(ron.contains("x: 1.0,"));
+        assert!(!ron.contains("Struct"));
+        let scene = DynamicScene::from_ron(&ron).unwrap();
+
+        let mut loaded = World::default();
+        loaded.spawn().add(Name("existing".to_string()));
+        let entity_map = scene.write_to_world(&mut loaded, &registry).unwrap();
+        let new_leader = entity_map[&leader];
+        assert_ne!(new_leader, leader);
+
+        let query = Query::<(&Name, &Follow)>::new();
+        let follows = query.iter(&loaded);
+        assert_eq!(follows.len(), 1);
+        assert_eq!(follows[0].0, &Name("follower".to_string()));
+        assert_eq!(follows[0].1.target, Some(new_leader));
+
+        let query = Query::<(&Name, &Position)>::new();
+        assert_eq!(
+            query.iter(&loaded),
+            vec![(&Name("leader".to_string()), &Position { x: 1., y: 2. })]
+        );
+    }
+
+    #[test]
+    fn hand_written() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Position>().register::<Follow>();
+
+        let scene = DynamicScene::from_ron(
+            r#"(
+                entities: [
+                    (entity: (id: 7, generation: 0), components: {
+                        "Position": Position(y: 3.0),
+                    }),
+                    (entity: (id: 8, generation: 0), components: {
+                        "Follow": (target: (id: 9, generation: 0)),
+                    }),
+                ],
+            )"#,
+        )
+        .unwrap();
+
+        let mut world = World::default();
+        assert!(matches!(
+            scene.write_to_world(&mut world, &registry),
+            Err(SceneError::UnknownEntity(_))
+        ));
+        assert!(world.entities().is_empty());
+
+        let scene = DynamicScene {
+            entities: scene.entities[..1].to_vec(),
+        };
+        let entity_map = scene.write_to_world(&mut world, &registry).unwrap();
+        let query = Query::<&Position>::new();
+        assert_eq!(query.iter(&world), vec![&Position { x: 0., y: 3. }]);
+        assert_eq!(entity_map.len(), 1);
+    }
+}
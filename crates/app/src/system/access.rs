@@ -0,0 +1,112 @@
+use std::{collections::HashSet, hash::Hash};
+
+use crate::{ComponentTypeId, ResourceTypeId};
+
+#[derive(Debug, Clone)]
+pub struct Access<T: Eq + Hash> {
+    reads: HashSet<T>,
+    writes: HashSet<T>,
+}
+
+impl<T: Eq + Hash> Default for Access<T> {
+    fn default() -> Self {
+        Access {
+            reads: HashSet::new(),
+            writes: HashSet::new(),
+        }
+    }
+}
+
+impl<T: Eq + Hash> Access<T> {
+    pub fn add_read(&mut self, id: T) {
+        self.reads.insert(id);
+    }
+
+    pub fn add_write(&mut self, id: T) {
+        self.writes.insert(id);
+    }
+
+    pub fn reads(&self) -> impl Iterator<Item = &T> {
+        self.reads.iter()
+    }
+
+    pub fn writes(&self) -> impl Iterator<Item = &T> {
+        self.writes.iter()
+    }
+
+    pub fn is_compatible(&self, other: &Access<T>) -> bool {
+        self.writes.is_disjoint(&other.writes)
+            && self.writes.is_disjoint(&other.reads)
+            && self.reads.is_disjoint(&other.writes)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SystemAccess {
+    resources: Access<ResourceTypeId>,
+    components: Access<ComponentTypeId>,
+    required_resources: Vec<ResourceTypeId>,
+    thread_local: bool,
+    exclusive: bool,
+}
+
+impl SystemAccess {
+    #[inline]
+    pub fn resources(&self) -> &Access<ResourceTypeId> {
+        &self.resources
+    }
+
+    #[inline]
+    pub fn resources_mut(&mut self) -> &mut Access<ResourceTypeId> {
+        &mut self.resources
+    }
+
+    #[inline]
+    pub fn components(&self) -> &Access<ComponentTypeId> {
+        &self.components
+    }
+
+    #[inline]
+    pub fn components_mut(&mut self) -> &mut Access<ComponentTypeId> {
+        &mut self.components
+    }
+
+    /// Resources that must exist before the system first runs.
+    #[inline]
+    pub fn required_resources(&self) -> &[ResourceTypeId] {
+        &self.required_resources
+    }
+
+    pub fn add_required_resource(&mut self, id: ResourceTypeId) {
+        if !self.required_resources.contains(&id) {
+            self.required_resources.push(id);
+        }
+    }
+
+    /// System touches a resource that must stay on the main thread.
+    #[inline]
+    pub fn is_thread_local(&self) -> bool {
+        self.thread_local
+    }
+
+    pub fn set_thread_local(&mut self) {
+        self.thread_local = true;
+    }
+
+    /// System may touch any component, so it never runs alongside another one.
+    #[inline]
+    pub fn is_exclusive(&self) -> bool {
+        self.exclusive
+    }
+
+    pub fn set_exclusive(&mut self) {
+        self.exclusive = true;
+    }
+
+    pub fn is_compatible(&self, other: &SystemAccess) -> bool {
+        !self.exclusive
+            && !other.exclusive
+            && self.resources.is_compatible(&other.resources)
+            && self.components.is_compatible(&other.components)
+    }
+}
@@ -1,6 +1,6 @@
 use std::{any::type_name, collections::VecDeque, fmt, marker::PhantomData, sync::Arc};
 
-use crate::{Component, Entity, World};
+use crate::{Component, Entity, Prefab, World};
 
 pub trait WorldWritable: Send + Sync {
     fn write(self: Arc<Self>, world: &mut World, cmd: &CommandBuffer);
@@ -46,6 +46,17 @@ impl<C: Component> WorldWritable for AddComponentCommand<C> {
     }
 }
 
+struct PrefabCommand {
+    entity: Entity,
+    prefab: Arc<Prefab>,
+}
+
+impl WorldWritable for PrefabCommand {
+    fn write(self: Arc<Self>, world: &mut World, _cmd: &CommandBuffer) {
+        self.prefab.write_to_entity(world, self.entity);
+    }
+}
+
 pub enum Command {
     WriteWorld(Arc<dyn WorldWritable>),
 }
@@ -61,7 +72,7 @@ impl CommandBuffer {
         }
     }
 
-    fn push_writer<W: 'static + WorldWritable>(&mut self, writer: W) {
+    pub fn push_writer<W: 'static + WorldWritable>(&mut self, writer: W) {
         self.commands
             .push_front(Command::WriteWorld(Arc::new(writer)));
     }
@@ -100,6 +111,7 @@ impl CommandBuffer {
     }
 
     pub fn flush(&mut self, world: &mut World) {
+        let _span = tracing::info_span!("flush", commands = self.commands.len()).entered();
         world.flush();
         while let Some(command) = self.commands.pop_back() {
             match command {
@@ -120,6 +132,16 @@ impl<'a> CommandEntityEditor<'a> {
         self
     }
 
+    /// Clone the prefab components onto the entity, components added after it
+    /// override the prefab ones.
+    pub fn add_prefab(&mut self, prefab: &Arc<Prefab>) -> &mut Self {
+        self.command_buffer.push_writer(PrefabCommand {
+            entity: self.entity,
+            prefab: prefab.clone(),
+        });
+        self
+    }
+
     pub fn remove<T: Component>(&mut self) -> &mut Self {
         self.command_buffer.remove_component::<T>(self.entity);
         self
@@ -0,0 +1,221 @@
+use std::{any::Any, error::Error, fmt};
+
+use crate::Resources;
+
+pub type BoxedError = Box<dyn Error + Send + Sync>;
+
+/// Return type of a system function, either `()` or a `Result`.
+pub trait IntoSystemResult {
+    fn into_system_result(self) -> Result<(), BoxedError>;
+}
+
+impl IntoSystemResult for () {
+    fn into_system_result(self) -> Result<(), BoxedError> {
+        Ok(())
+    }
+}
+
+impl<E> IntoSystemResult for Result<(), E>
+where
+    E: Into<BoxedError>,
+{
+    fn into_system_result(self) -> Result<(), BoxedError> {
+        self.map_err(Into::into)
+    }
+}
+
+#[derive(Debug)]
+pub enum SystemErrorKind {
+    Returned(BoxedError),
+    /// Only reported when `SystemErrorHandling::catch_panics` is set.
+    Panicked(String),
+}
+
+#[derive(Debug)]
+pub struct SystemError {
+    pub system: String,
+    pub kind: SystemErrorKind,
+}
+
+impl SystemError {
+    pub(crate) fn panicked(system: &str, payload: Box<dyn Any + Send>) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic payload".to_string()
+        };
+        SystemError {
+            system: system.to_string(),
+            kind: SystemErrorKind::Panicked(message),
+        }
+    }
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            SystemErrorKind::Returned(err) => write!(f, "system '{}' failed: {}", self.system, err),
+            SystemErrorKind::Panicked(message) => {
+                write!(f, "system '{}' panicked: {}", self.system, message)
+            }
+        }
+    }
+}
+
+impl Error for SystemError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnSystemError {
+    /// Log the error with `tracing` and keep running.
+    Log,
+    Panic,
+    /// Push the error to the `SystemErrors` resource.
+    Collect,
+}
+
+/// Resource configuring how stages handle system errors, once every system of
+/// the stage ran. Without it errors are logged and panics are not caught.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemErrorHandling {
+    pub on_error: OnSystemError,
+    /// Catch system panics and handle them as errors, so the rest of the frame
+    /// keeps running. Meant for development builds.
+    pub catch_panics: bool,
+}
+
+impl Default for SystemErrorHandling {
+    fn default() -> Self {
+        SystemErrorHandling {
+            on_error: OnSystemError::Log,
+            catch_panics: false,
+        }
+    }
+}
+
+impl SystemErrorHandling {
+    pub(crate) fn handle(&self, error: SystemError, resources: &mut Resources) {
+        match self.on_error {
+            OnSystemError::Log => match &error.kind {
+                SystemErrorKind::Returned(err) => {
+                    tracing::error!(system = %error.system, "system failed: {}", err)
+                }
+                SystemErrorKind::Panicked(message) => {
+                    tracing::error!(system = %error.system, "system panicked: {}", message)
+                }
+            },
+            OnSystemError::Panic => panic!("{}", error),
+            OnSystemError::Collect => resources
+                .get_mut_or_default::<SystemErrors>()
+                .errors
+                .push(error),
+        }
+    }
+}
+
+/// Errors collected with `OnSystemError::Collect`.
+#[derive(Debug, Default)]
+pub struct SystemErrors {
+    errors: Vec<SystemError>,
+}
+
+impl SystemErrors {
+    pub fn iter(&self) -> impl Iterator<Item = &SystemError> {
+        self.errors.iter()
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = SystemError> + '_ {
+        self.errors.drain(..)
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Stage, SystemBuilder, World};
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct NotFound;
+
+    impl fmt::Display for NotFound {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "not found")
+        }
+    }
+
+    impl Error for NotFound {}
+
+    #[derive(Default)]
+    struct Ran(u32);
+
+    #[test]
+    fn system_errors() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(SystemErrorHandling {
+            on_error: OnSystemError::Collect,
+            catch_panics: true,
+        });
+        resources.insert(Ran::default());
+
+        let mut stage = Stage::sequence();
+        stage
+            .add_system(
+                SystemBuilder::new()
+                    .name("lookup")
+                    .build(|_, _, _, _| Err(NotFound)),
+            )
+            .add_system(
+                SystemBuilder::new()
+                    .name("explode")
+                    .build(|_, _, _, _| -> Result<(), NotFound> { panic!("boom") }),
+            )
+            .add_system(SystemBuilder::new().write_resource::<Ran>().build(
+                |_, _, ran, _| -> Result<(), BoxedError> {
+                    ran.0 += 1;
+                    Ok(())
+                },
+            ));
+        stage.run(&mut world, &mut resources);
+
+        assert_eq!(resources.get::<Ran>().unwrap().0, 1);
+        let errors = resources.get::<SystemErrors>().unwrap();
+        let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "system 'lookup' failed: not found",
+                "system 'explode' panicked: boom",
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "system 'lookup' failed: not found")]
+    fn panic_on_error() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(SystemErrorHandling {
+            on_error: OnSystemError::Panic,
+            catch_panics: false,
+        });
+
+        let mut stage = Stage::sequence();
+        stage.add_system(
+            SystemBuilder::new()
+                .name("lookup")
+                .build(|_, _, _, _| Err(NotFound)),
+        );
+        stage.run(&mut world, &mut resources);
+    }
+}
@@ -1,39 +1,139 @@
-use std::cell::UnsafeCell;
+use std::{
+    cell::UnsafeCell,
+    panic::{self, AssertUnwindSafe},
+};
+#[cfg(feature = "diagnostics")]
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
 
-use util::downcast_rs::{impl_downcast, Downcast};
+use util::{
+    downcast_rs::{impl_downcast, Downcast},
+    rayon,
+};
 
-use crate::{BoxedStageLabel, CommandBuffer, RawResources, Resources, World};
+use crate::{
+    BoxedError, BoxedStageLabel, CommandBuffer, RawResources, Resources, SystemAccess, SystemError,
+    SystemErrorKind, World,
+};
 
 pub trait ParRunnable: Runnable + Send + Sync {}
 impl<T: Runnable + Send + Sync> ParRunnable for T {}
 
 pub trait Runnable {
-    unsafe fn run_unsafe(&mut self, world: &World, resources: &RawResources);
+    unsafe fn run_unsafe(
+        &mut self,
+        world: &World,
+        resources: &RawResources,
+    ) -> Result<(), BoxedError>;
 
     fn command_buffer_mut(&mut self) -> Option<&mut CommandBuffer>;
 
     fn stage(&self) -> Option<BoxedStageLabel>;
 
-    fn run(&mut self, world: &World, resources: &mut Resources) {
+    fn access(&self) -> &SystemAccess;
+
+    fn name(&self) -> &str;
+
+    fn run(&mut self, world: &World, resources: &mut Resources) -> Result<(), BoxedError> {
         unsafe { self.run_unsafe(world, resources.internal()) }
     }
 }
 
-pub(crate) struct SystemBox(UnsafeCell<Box<dyn ParRunnable>>);
+pub(crate) struct SystemBox {
+    system: UnsafeCell<Box<dyn ParRunnable>>,
+    error: UnsafeCell<Option<SystemError>>,
+    pub(crate) catch_panics: bool,
+    /// Nanoseconds spent in the last run, `u64::MAX` once taken.
+    #[cfg(feature = "diagnostics")]
+    duration: AtomicU64,
+}
 unsafe impl Send for SystemBox {}
 unsafe impl Sync for SystemBox {}
 
 impl SystemBox {
     pub(crate) fn new<S: ParRunnable + 'static>(system: S) -> Self {
-        SystemBox(UnsafeCell::new(Box::new(system)))
+        SystemBox {
+            system: UnsafeCell::new(Box::new(system)),
+            error: UnsafeCell::new(None),
+            catch_panics: false,
+            #[cfg(feature = "diagnostics")]
+            duration: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    pub(crate) unsafe fn get(&self) -> &dyn ParRunnable {
+        std::ops::Deref::deref(&*self.system.get())
     }
 
     pub(crate) unsafe fn get_mut(&self) -> &mut dyn ParRunnable {
-        std::ops::DerefMut::deref_mut(&mut *self.0.get())
+        std::ops::DerefMut::deref_mut(&mut *self.system.get())
+    }
+
+    /// # Safety
+    /// Same as `Runnable::run_unsafe`, and the system must not be borrowed
+    /// anywhere else.
+    pub(crate) unsafe fn run(&self, world: &World, resources: &RawResources) {
+        let system = self.get_mut();
+        let _span = tracing::info_span!("system", name = system.name()).entered();
+        #[cfg(feature = "diagnostics")]
+        let start = Instant::now();
+        let result = match panic::catch_unwind(AssertUnwindSafe(|| {
+            system.run_unsafe(world, resources)
+        })) {
+            Ok(result) => result.map_err(|err| SystemError {
+                system: system.name().to_string(),
+                kind: SystemErrorKind::Returned(err),
+            }),
+            Err(payload) if self.catch_panics => Err(SystemError::panicked(system.name(), payload)),
+            Err(payload) => {
+                tracing::error!(system = system.name(), "system panicked");
+                panic::resume_unwind(payload)
+            }
+        };
+        #[cfg(feature = "diagnostics")]
+        self.duration
+            .store(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        if let Err(err) = result {
+            *self.error.get() = Some(err);
+        }
+    }
+
+    /// Error of the last run, if the system failed.
+    pub(crate) fn take_error(&mut self) -> Option<SystemError> {
+        self.error.get_mut().take()
+    }
+
+    /// Duration of the run since the last call, if the system ran.
+    #[cfg(feature = "diagnostics")]
+    pub(crate) fn take_duration(&self) -> Option<Duration> {
+        match self.duration.swap(u64::MAX, Ordering::Relaxed) {
+            u64::MAX => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExecutorKind {
+    Sequence,
+    SequenceOnce,
+    Parallel,
+}
+
+impl std::fmt::Display for ExecutorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutorKind::Sequence => write!(f, "sequence"),
+            ExecutorKind::SequenceOnce => write!(f, "sequence once"),
+            ExecutorKind::Parallel => write!(f, "parallel"),
+        }
     }
 }
 
 pub(crate) trait Executor: Downcast + Send + Sync {
+    fn kind(&self) -> ExecutorKind;
     fn cache_data(&mut self, systems: &[SystemBox]);
     fn run_systems(&mut self, systems: &[SystemBox], world: &mut World, resources: &RawResources);
 }
@@ -43,12 +143,15 @@ impl_downcast!(Executor);
 pub struct SequenceExecutor {}
 
 impl Executor for SequenceExecutor {
+    fn kind(&self) -> ExecutorKind {
+        ExecutorKind::Sequence
+    }
+
     fn cache_data(&mut self, _systems: &[SystemBox]) {}
 
     fn run_systems(&mut self, systems: &[SystemBox], world: &mut World, resources: &RawResources) {
         for system in systems {
-            let borrow = unsafe { system.get_mut() };
-            unsafe { borrow.run_unsafe(world, resources) }
+            unsafe { system.run(world, resources) }
         }
     }
 }
@@ -59,6 +162,10 @@ pub struct SequenceOnceExecutor {
 }
 
 impl Executor for SequenceOnceExecutor {
+    fn kind(&self) -> ExecutorKind {
+        ExecutorKind::SequenceOnce
+    }
+
     fn cache_data(&mut self, _systems: &[SystemBox]) {}
 
     fn run_systems(&mut self, systems: &[SystemBox], world: &mut World, resources: &RawResources) {
@@ -67,9 +174,133 @@ impl Executor for SequenceOnceExecutor {
         }
 
         for system in systems {
-            let borrow = unsafe { system.get_mut() };
-            unsafe { borrow.run_unsafe(world, resources) }
+            unsafe { system.run(world, resources) }
         }
         self.ran = true;
     }
 }
+
+/// Run systems in batches on the rayon thread pool. Systems whose data access
+/// conflicts keep their insertion order, thread local systems always run on the
+/// thread calling the stage.
+#[derive(Default)]
+pub struct ParallelExecutor {
+    batches: Vec<Vec<usize>>,
+}
+
+impl Executor for ParallelExecutor {
+    fn kind(&self) -> ExecutorKind {
+        ExecutorKind::Parallel
+    }
+
+    fn cache_data(&mut self, systems: &[SystemBox]) {
+        self.batches.clear();
+
+        let mut system_batches: Vec<usize> = Vec::with_capacity(systems.len());
+        for (index, system) in systems.iter().enumerate() {
+            let access = unsafe { system.get() }.access();
+            let batch = system_batches
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| {
+                    !access.is_compatible(unsafe { systems[*other].get() }.access())
+                })
+                .map(|(_, batch)| batch + 1)
+                .max()
+                .unwrap_or(0);
+
+            if batch == self.batches.len() {
+                self.batches.push(Vec::new());
+            }
+            self.batches[batch].push(index);
+            system_batches.push(batch);
+        }
+    }
+
+    fn run_systems(&mut self, systems: &[SystemBox], world: &mut World, resources: &RawResources) {
+        let world: &World = world;
+        for batch in self.batches.iter() {
+            rayon::in_place_scope(|scope| {
+                let mut thread_local = Vec::new();
+                for &index in batch {
+                    let system = &systems[index];
+                    if unsafe { system.get() }.access().is_thread_local() {
+                        thread_local.push(system);
+                    } else {
+                        scope.spawn(move |_| unsafe { system.run(world, resources) });
+                    }
+                }
+
+                for system in thread_local {
+                    unsafe { system.run(world, resources) }
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        marker::PhantomData,
+        sync::atomic::{AtomicUsize, Ordering},
+        thread::{self, ThreadId},
+    };
+
+    use crate::{Stage, SystemBuilder};
+
+    use super::*;
+
+    struct MainThreadOnly {
+        ran_on: Vec<ThreadId>,
+        _not_send_sync: PhantomData<*const u8>,
+    }
+
+    #[derive(Default)]
+    struct Counter(AtomicUsize);
+
+    #[test]
+    fn parallel_executor() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(MainThreadOnly {
+            ran_on: Vec::new(),
+            _not_send_sync: PhantomData,
+        });
+        resources.insert(Counter::default());
+
+        let mut stage = Stage::parallel();
+        for _ in 0..4 {
+            stage.add_system(SystemBuilder::new().read_resource::<Counter>().build(
+                |_, _, counter, _| {
+                    counter.0.fetch_add(1, Ordering::Relaxed);
+                },
+            ));
+            stage.add_system(
+                SystemBuilder::new()
+                    .write_non_send_resource::<MainThreadOnly>()
+                    .build(|_, _, main_thread_only, _| {
+                        main_thread_only.ran_on.push(thread::current().id());
+                    }),
+            );
+        }
+
+        stage.run(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        assert_eq!(
+            resources
+                .get::<Counter>()
+                .unwrap()
+                .0
+                .load(Ordering::Relaxed),
+            8
+        );
+        let main_thread_only = resources.get::<MainThreadOnly>().unwrap();
+        assert_eq!(main_thread_only.ran_on.len(), 8);
+        assert!(main_thread_only
+            .ran_on
+            .iter()
+            .all(|id| *id == thread::current().id()));
+    }
+}
@@ -52,6 +52,11 @@ pub trait StageLabel: DynHash + Debug + Send + Sync + 'static {
 }
 pub(crate) type BoxedStageLabel = Box<dyn StageLabel>;
 
+pub trait AppLabel: DynHash + Debug + Send + Sync + 'static {
+    fn dyn_clone(&self) -> Box<dyn AppLabel>;
+}
+pub(crate) type BoxedAppLabel = Box<dyn AppLabel>;
+
 macro_rules! impl_label {
     ($trait_name:ident) => {
         impl PartialEq for dyn $trait_name {
@@ -80,6 +85,12 @@ macro_rules! impl_label {
             }
         }
 
+        impl $trait_name for Box<dyn $trait_name> {
+            fn dyn_clone(&self) -> Box<dyn $trait_name> {
+                (**self).dyn_clone()
+            }
+        }
+
         impl $trait_name for &'static str {
             fn dyn_clone(&self) -> Box<dyn $trait_name> {
                 Box::new(<&str>::clone(self))
@@ -89,3 +100,4 @@ macro_rules! impl_label {
 }
 
 impl_label!(StageLabel);
+impl_label!(AppLabel);
@@ -1,4 +1,6 @@
+pub mod access;
 pub mod command;
+pub mod error;
 pub mod executor;
 pub mod label;
 pub mod resources;
@@ -6,7 +8,9 @@ pub mod schedule;
 pub mod stage;
 pub mod system;
 
+pub use access::*;
 pub use command::*;
+pub use error::*;
 pub use executor::*;
 pub use label::*;
 pub use resources::*;
@@ -5,6 +5,8 @@ use std::{
     fmt::Display,
     hash::Hash,
     marker::PhantomData,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use util::{
@@ -12,7 +14,7 @@ use util::{
     downcast_rs::{impl_downcast, Downcast},
 };
 
-use crate::{Read, Write};
+use crate::{NonSend, NonSendMut, Read, SystemAccess, TryRead, TryWrite, Write};
 
 #[derive(Debug, Clone, Copy, Eq, PartialOrd, Ord)]
 pub struct ResourceTypeId {
@@ -62,37 +64,184 @@ pub trait Resource: 'static + Downcast {}
 impl<T> Resource for T where T: 'static {}
 impl_downcast!(Resource);
 
+/// Change ticks of the system fetching the resources.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemTicks {
+    pub last_run: u64,
+    pub this_run: u64,
+}
+
+pub struct Res<'a, T> {
+    value: AtomicRef<'a, T>,
+    added: u64,
+    changed: u64,
+    last_run: u64,
+}
+
+impl<'a, T> Res<'a, T> {
+    /// Resource was inserted since the last time the system ran.
+    pub fn is_added(&self) -> bool {
+        self.added > self.last_run
+    }
+
+    /// Resource was mutably borrowed since the last time the system ran.
+    pub fn is_changed(&self) -> bool {
+        self.changed > self.last_run
+    }
+
+    pub fn into_inner(self) -> AtomicRef<'a, T> {
+        self.value
+    }
+}
+
+impl<'a, T> Deref for Res<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+pub struct ResMut<'a, T> {
+    value: AtomicRefMut<'a, T>,
+    added: u64,
+    changed: u64,
+    last_run: u64,
+}
+
+impl<'a, T> ResMut<'a, T> {
+    /// Resource was inserted since the last time the system ran.
+    pub fn is_added(&self) -> bool {
+        self.added > self.last_run
+    }
+
+    /// Resource was mutably borrowed elsewhere since the last time the system ran.
+    pub fn is_changed(&self) -> bool {
+        self.changed > self.last_run
+    }
+
+    pub fn into_inner(self) -> AtomicRefMut<'a, T> {
+        self.value
+    }
+}
+
+impl<'a, T> Deref for ResMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<'a, T> DerefMut for ResMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
 pub trait ResourceSet<'a> {
     type Item: 'a;
-    unsafe fn fetch(resources: &'a RawResources) -> Self::Item;
+    unsafe fn fetch(resources: &'a RawResources, ticks: SystemTicks) -> Self::Item;
+    fn access(access: &mut SystemAccess);
 }
 
 impl<'a> ResourceSet<'a> for () {
     type Item = ();
-    unsafe fn fetch(_resources: &'a RawResources) -> Self::Item {}
+    unsafe fn fetch(_resources: &'a RawResources, _ticks: SystemTicks) -> Self::Item {}
+    fn access(_access: &mut SystemAccess) {}
 }
 
-impl<'a, T: Resource> ResourceSet<'a> for Read<T> {
-    type Item = AtomicRef<'a, T>;
-    unsafe fn fetch(resources: &'a RawResources) -> Self::Item {
+impl<'a, T: Resource + Sync> ResourceSet<'a> for Read<T> {
+    type Item = Res<'a, T>;
+    unsafe fn fetch(resources: &'a RawResources, ticks: SystemTicks) -> Self::Item {
         let type_id = &ResourceTypeId::of::<T>();
         resources
             .get(type_id)
-            .map(|x| x.get::<T>())
+            .map(|x| x.res::<T>(ticks))
             .unwrap_or_else(|| panic_nonexistent_resource(type_id))
     }
+
+    fn access(access: &mut SystemAccess) {
+        access.resources_mut().add_read(ResourceTypeId::of::<T>());
+        access.add_required_resource(ResourceTypeId::of::<T>());
+    }
 }
 
-impl<'a, T: Resource> ResourceSet<'a> for Write<T> {
-    type Item = AtomicRefMut<'a, T>;
+impl<'a, T: Resource + Send> ResourceSet<'a> for Write<T> {
+    type Item = ResMut<'a, T>;
 
-    unsafe fn fetch(resources: &'a RawResources) -> Self::Item {
+    unsafe fn fetch(resources: &'a RawResources, ticks: SystemTicks) -> Self::Item {
         let type_id = &ResourceTypeId::of::<T>();
         resources
             .get(&type_id)
-            .map(|x| x.get_mut::<T>())
+            .map(|x| x.res_mut::<T>(ticks))
+            .unwrap_or_else(|| panic_nonexistent_resource(type_id))
+    }
+
+    fn access(access: &mut SystemAccess) {
+        access.resources_mut().add_write(ResourceTypeId::of::<T>());
+        access.add_required_resource(ResourceTypeId::of::<T>());
+    }
+}
+
+impl<'a, T: Resource> ResourceSet<'a> for NonSend<T> {
+    type Item = Res<'a, T>;
+    unsafe fn fetch(resources: &'a RawResources, ticks: SystemTicks) -> Self::Item {
+        let type_id = &ResourceTypeId::of::<T>();
+        resources
+            .get(type_id)
+            .map(|x| x.res::<T>(ticks))
+            .unwrap_or_else(|| panic_nonexistent_resource(type_id))
+    }
+
+    fn access(access: &mut SystemAccess) {
+        access.resources_mut().add_read(ResourceTypeId::of::<T>());
+        access.add_required_resource(ResourceTypeId::of::<T>());
+        access.set_thread_local();
+    }
+}
+
+impl<'a, T: Resource> ResourceSet<'a> for NonSendMut<T> {
+    type Item = ResMut<'a, T>;
+    unsafe fn fetch(resources: &'a RawResources, ticks: SystemTicks) -> Self::Item {
+        let type_id = &ResourceTypeId::of::<T>();
+        resources
+            .get(type_id)
+            .map(|x| x.res_mut::<T>(ticks))
             .unwrap_or_else(|| panic_nonexistent_resource(type_id))
     }
+
+    fn access(access: &mut SystemAccess) {
+        access.resources_mut().add_write(ResourceTypeId::of::<T>());
+        access.add_required_resource(ResourceTypeId::of::<T>());
+        access.set_thread_local();
+    }
+}
+
+impl<'a, T: Resource + Sync> ResourceSet<'a> for TryRead<T> {
+    type Item = Option<Res<'a, T>>;
+    unsafe fn fetch(resources: &'a RawResources, ticks: SystemTicks) -> Self::Item {
+        resources
+            .get(&ResourceTypeId::of::<T>())
+            .map(|x| x.res::<T>(ticks))
+    }
+
+    fn access(access: &mut SystemAccess) {
+        access.resources_mut().add_read(ResourceTypeId::of::<T>());
+    }
+}
+
+impl<'a, T: Resource + Send> ResourceSet<'a> for TryWrite<T> {
+    type Item = Option<ResMut<'a, T>>;
+    unsafe fn fetch(resources: &'a RawResources, ticks: SystemTicks) -> Self::Item {
+        resources
+            .get(&ResourceTypeId::of::<T>())
+            .map(|x| x.res_mut::<T>(ticks))
+    }
+
+    fn access(access: &mut SystemAccess) {
+        access.resources_mut().add_write(ResourceTypeId::of::<T>());
+    }
 }
 
 fn panic_nonexistent_resource(type_id: &ResourceTypeId) -> ! {
@@ -118,8 +267,12 @@ macro_rules! impl_resource_tuple {
         impl<'a, $($ty: ResourceSet<'a>),*> ResourceSet<'a> for ($($ty,)*)
         {
             type Item = ($($ty::Item,)*);
-            unsafe fn fetch(resources: &'a RawResources) -> Self::Item {
-                ($( $ty::fetch(resources), )*)
+            unsafe fn fetch(resources: &'a RawResources, ticks: SystemTicks) -> Self::Item {
+                ($( $ty::fetch(resources, ticks), )*)
+            }
+
+            fn access(access: &mut SystemAccess) {
+                $( $ty::access(access); )*
             }
         }
     };
@@ -128,15 +281,29 @@ resource_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V
 
 pub struct ResourceCell {
     data: AtomicRefCell<Box<dyn Resource>>,
+    added: u64,
+    changed: AtomicU64,
 }
 
 impl ResourceCell {
-    fn new(resource: Box<dyn Resource>) -> Self {
+    fn new(resource: Box<dyn Resource>, change_tick: u64) -> Self {
         ResourceCell {
             data: AtomicRefCell::new(resource),
+            added: change_tick,
+            changed: AtomicU64::new(change_tick),
         }
     }
 
+    #[inline]
+    pub fn added_tick(&self) -> u64 {
+        self.added
+    }
+
+    #[inline]
+    pub fn changed_tick(&self) -> u64 {
+        self.changed.load(Ordering::Acquire)
+    }
+
     fn into_inner(self) -> Box<dyn Resource> {
         self.data.into_inner()
     }
@@ -146,20 +313,62 @@ impl ResourceCell {
         AtomicRef::map(borrow, |inner| inner.downcast_ref::<T>().unwrap())
     }
 
-    pub fn get_mut<T: Resource>(&self) -> AtomicRefMut<T> {
+    /// Mutably borrow the resource, marking it as changed at `change_tick`.
+    pub fn get_mut<T: Resource>(&self, change_tick: u64) -> AtomicRefMut<T> {
         let borrow = self.data.borrow_mut();
+        self.changed.store(change_tick, Ordering::Release);
         AtomicRefMut::map(borrow, |inner| inner.downcast_mut::<T>().unwrap())
     }
+
+    fn res<T: Resource>(&self, ticks: SystemTicks) -> Res<'_, T> {
+        Res {
+            value: self.get::<T>(),
+            added: self.added,
+            changed: self.changed_tick(),
+            last_run: ticks.last_run,
+        }
+    }
+
+    fn res_mut<T: Resource>(&self, ticks: SystemTicks) -> ResMut<'_, T> {
+        let changed = self.changed_tick();
+        ResMut {
+            value: self.get_mut::<T>(ticks.this_run),
+            added: self.added,
+            changed,
+            last_run: ticks.last_run,
+        }
+    }
 }
 
-#[derive(Default)]
 pub struct RawResources {
     map: HashMap<ResourceTypeId, ResourceCell>,
+    change_tick: AtomicU64,
 }
 unsafe impl Send for RawResources {}
 unsafe impl Sync for RawResources {}
 
+impl Default for RawResources {
+    fn default() -> Self {
+        RawResources {
+            map: HashMap::new(),
+            // systems start with `last_run` 0, anything inserted before them counts as added
+            change_tick: AtomicU64::new(1),
+        }
+    }
+}
+
 impl RawResources {
+    #[inline]
+    pub fn change_tick(&self) -> u64 {
+        self.change_tick.load(Ordering::Acquire)
+    }
+
+    /// Take the current change tick for a system run and advance the counter,
+    /// so changes made after the run are seen as newer than it.
+    pub(crate) fn increment_change_tick(&self) -> u64 {
+        self.change_tick.fetch_add(1, Ordering::AcqRel)
+    }
+
     fn contains(&self, type_id: &ResourceTypeId) -> bool {
         self.map.contains_key(type_id)
     }
@@ -169,9 +378,10 @@ impl RawResources {
     }
 
     unsafe fn insert<T: Resource>(&mut self, resource: T) {
+        let change_tick = self.change_tick();
         self.map.insert(
             ResourceTypeId::of::<T>(),
-            ResourceCell::new(Box::new(resource)),
+            ResourceCell::new(Box::new(resource), change_tick),
         );
     }
 
@@ -211,6 +421,10 @@ impl Resources {
         self.internal.contains(&ResourceTypeId::of::<T>())
     }
 
+    pub fn contains_raw(&self, type_id: &ResourceTypeId) -> bool {
+        self.internal.contains(type_id)
+    }
+
     pub fn insert<T: Resource>(&mut self, value: T) {
         unsafe {
             self.internal.insert(value);
@@ -235,15 +449,19 @@ impl Resources {
 
     pub fn get_mut<T: Resource>(&self) -> Option<AtomicRefMut<T>> {
         let type_id = &ResourceTypeId::of::<T>();
-        self.internal.get(&type_id).map(|x| x.get_mut::<T>())
+        let change_tick = self.internal.change_tick();
+        self.internal
+            .get(&type_id)
+            .map(|x| x.get_mut::<T>(change_tick))
     }
 
     pub fn get_or_insert_with<T: Resource, F: FnOnce() -> T>(&mut self, f: F) -> AtomicRef<T> {
         let type_id = ResourceTypeId::of::<T>();
+        let change_tick = self.internal.change_tick();
         unsafe {
             self.internal
                 .entry(type_id)
-                .or_insert_with(|| ResourceCell::new(Box::new((f)())))
+                .or_insert_with(|| ResourceCell::new(Box::new((f)()), change_tick))
                 .get()
         }
     }
@@ -253,11 +471,12 @@ impl Resources {
         f: F,
     ) -> AtomicRefMut<T> {
         let type_id = ResourceTypeId::of::<T>();
+        let change_tick = self.internal.change_tick();
         unsafe {
             self.internal
                 .entry(type_id)
-                .or_insert_with(|| ResourceCell::new(Box::new((f)())))
-                .get_mut()
+                .or_insert_with(|| ResourceCell::new(Box::new((f)()), change_tick))
+                .get_mut(change_tick)
         }
     }
 
@@ -296,6 +515,61 @@ impl<'a> SyncResources<'a> {
 
     pub fn get_mut<T: Resource + Send>(&self) -> Option<AtomicRefMut<T>> {
         let type_id = &ResourceTypeId::of::<T>();
-        self.internal.get(&type_id).map(|x| x.get_mut::<T>())
+        let change_tick = self.internal.change_tick();
+        self.internal
+            .get(&type_id)
+            .map(|x| x.get_mut::<T>(change_tick))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{Runnable, SystemBuilder, World};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Settings(i32);
+
+    #[test]
+    fn change_detection() {
+        let world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Settings(0));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut reader = SystemBuilder::new().read_resource::<Settings>().build({
+            let seen = seen.clone();
+            move |_, _, settings, _| {
+                seen.lock()
+                    .unwrap()
+                    .push((settings.is_added(), settings.is_changed()))
+            }
+        });
+        let mut writer = SystemBuilder::new()
+            .write_resource::<Settings>()
+            .build(|_, _, settings, _| settings.0 += 1);
+
+        reader.run(&world, &mut resources).unwrap();
+        reader.run(&world, &mut resources).unwrap();
+        writer.run(&world, &mut resources).unwrap();
+        reader.run(&world, &mut resources).unwrap();
+        resources.get_mut::<Settings>().unwrap().0 += 1;
+        reader.run(&world, &mut resources).unwrap();
+        reader.run(&world, &mut resources).unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                (true, true),
+                (false, false),
+                (false, true),
+                (false, true),
+                (false, false)
+            ]
+        );
+        assert_eq!(resources.get::<Settings>().unwrap().0, 2);
     }
 }
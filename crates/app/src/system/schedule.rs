@@ -1,11 +1,46 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+};
 
-use crate::{system::stage::Stage, BoxedStageLabel, ParRunnable, Resources, StageLabel, World};
+use crate::{
+    system::stage::Stage, BoxedStageLabel, ExecutorKind, ParRunnable, ResourceTypeId, Resources,
+    StageLabel, SystemAccess, World,
+};
+
+#[derive(Debug)]
+pub struct MissingResourcesError {
+    missing: Vec<(BoxedStageLabel, ResourceTypeId, String)>,
+}
+
+impl MissingResourcesError {
+    /// Stage, resource and name of the system requiring it.
+    pub fn missing(&self) -> &[(BoxedStageLabel, ResourceTypeId, String)] {
+        &self.missing
+    }
+}
+
+impl fmt::Display for MissingResourcesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "systems require resources that do not exist:")?;
+        for (stage, resource, system) in self.missing.iter() {
+            write!(
+                f,
+                "\n  - {} (system '{}', stage {:?})",
+                resource, system, stage
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MissingResourcesError {}
 
 #[derive(Default)]
 pub struct Schedule {
     stages: HashMap<BoxedStageLabel, Stage>,
     stage_order: Vec<BoxedStageLabel>,
+    validated: bool,
 }
 
 impl Schedule {
@@ -22,6 +57,7 @@ impl Schedule {
     }
 
     pub fn add_stage(&mut self, label: impl StageLabel, stage: Stage) -> &mut Self {
+        self.validated = false;
         let label: Box<dyn StageLabel> = Box::new(label);
         self.stage_order.push(label.clone());
         if self.stages.insert(label.clone(), stage).is_some() {
@@ -36,6 +72,7 @@ impl Schedule {
         label: impl StageLabel,
         stage: Stage,
     ) -> &mut Self {
+        self.validated = false;
         let label: Box<dyn StageLabel> = Box::new(label);
         let target_index = self
             .get_stage_index(target.dyn_clone().as_ref())
@@ -53,6 +90,7 @@ impl Schedule {
         label: impl StageLabel,
         stage: Stage,
     ) -> &mut Self {
+        self.validated = false;
         let label: Box<dyn StageLabel> = Box::new(label);
         let target_index = self
             .get_stage_index(target.dyn_clone().as_ref())
@@ -92,17 +130,187 @@ impl Schedule {
             .get_stage_mut(label)
             .unwrap_or_else(move || panic!("Stage '{:?}' does not exist", label));
         stage.add_system(system);
+        self.validated = false;
         self
     }
 
+    /// Stage labels in execution order.
+    pub fn stage_order(&self) -> &[BoxedStageLabel] {
+        &self.stage_order
+    }
+
+    pub fn stage(&self, label: impl StageLabel) -> Option<&Stage> {
+        self.stages.get(&label.dyn_clone())
+    }
+
+    /// Stages in execution order.
+    pub fn stages(&self) -> impl Iterator<Item = (&dyn StageLabel, &Stage)> {
+        self.stage_order
+            .iter()
+            .map(move |label| (label.as_ref(), &self.stages[label]))
+    }
+
+    /// Render the stages and their systems as a Graphviz graph. Systems of a
+    /// sequence stage are chained in order, conflicting systems of a parallel
+    /// stage are linked by a dashed edge from the one running first.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph schedule {{").unwrap();
+        writeln!(dot, "  compound=true;").unwrap();
+        writeln!(dot, "  node [shape=box];").unwrap();
+
+        for (stage_index, (label, stage)) in self.stages().enumerate() {
+            let kind = stage.executor_kind();
+            writeln!(dot, "  subgraph cluster_{} {{", stage_index).unwrap();
+            writeln!(
+                dot,
+                "    label=\"{}\";",
+                escape(&format!("{:?} ({})", label, kind))
+            )
+            .unwrap();
+            writeln!(dot, "    stage_{} [shape=point, style=invis];", stage_index).unwrap();
+
+            let systems: Vec<&dyn ParRunnable> = stage.systems().collect();
+            for (index, system) in systems.iter().enumerate() {
+                writeln!(
+                    dot,
+                    "    s{}_{} [label=\"{}\"];",
+                    stage_index,
+                    index,
+                    escape(&system_label(system.name(), system.access()))
+                )
+                .unwrap();
+            }
+
+            for (index, system) in systems.iter().enumerate() {
+                if kind == ExecutorKind::Parallel {
+                    for (before_index, before) in systems[..index].iter().enumerate() {
+                        if !system.access().is_compatible(before.access()) {
+                            writeln!(
+                                dot,
+                                "    s{0}_{1} -> s{0}_{2} [style=dashed, color=red];",
+                                stage_index, before_index, index
+                            )
+                            .unwrap();
+                        }
+                    }
+                } else if index > 0 {
+                    writeln!(
+                        dot,
+                        "    s{0}_{1} -> s{0}_{2};",
+                        stage_index,
+                        index - 1,
+                        index
+                    )
+                    .unwrap();
+                }
+            }
+            writeln!(dot, "  }}").unwrap();
+
+            if stage_index > 0 {
+                writeln!(
+                    dot,
+                    "  stage_{0} -> stage_{1} [ltail=cluster_{0}, lhead=cluster_{1}];",
+                    stage_index - 1,
+                    stage_index
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+    /// Check that every resource required by the systems exists.
+    pub fn validate(&self, resources: &Resources) -> Result<(), MissingResourcesError> {
+        let mut missing = Vec::new();
+        for label in self.stage_order.iter() {
+            for system in self.stages[label].systems() {
+                for resource in system.access().required_resources() {
+                    if !resources.contains_raw(resource) {
+                        missing.push((label.clone(), *resource, system.name().to_string()));
+                    }
+                }
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingResourcesError { missing })
+        }
+    }
+
     pub fn run(&mut self, world: &mut World, resources: &mut Resources) {
+        if !self.validated {
+            if let Err(err) = self.validate(resources) {
+                panic!("{}", err);
+            }
+            self.validated = true;
+        }
+
+        let _span = tracing::info_span!("schedule").entered();
+        #[cfg(feature = "diagnostics")]
+        let mut timings = Vec::with_capacity(self.stage_order.len());
         for label in self.stage_order.iter() {
             let stage = self.stages.get_mut(label).unwrap();
-            stage.run(world, resources);
+            {
+                let _span = tracing::info_span!("stage", label = ?label).entered();
+                stage.run(world, resources);
+            }
+            #[cfg(feature = "diagnostics")]
+            timings.push(crate::StageTiming {
+                stage: label.clone(),
+                duration: stage.last_duration(),
+                systems: stage.take_system_timings(),
+            });
+        }
+
+        #[cfg(feature = "diagnostics")]
+        if let Some(mut diagnostics) = resources.get_mut::<crate::Diagnostics>() {
+            diagnostics.set_stage_timings(timings);
         }
     }
 }
 
+fn system_label(name: &str, access: &SystemAccess) -> String {
+    let mut label = name.to_string();
+    let mut reads: Vec<String> = access
+        .resources()
+        .reads()
+        .map(ToString::to_string)
+        .collect();
+    reads.extend(access.components().reads().map(ToString::to_string));
+    let mut writes: Vec<String> = access
+        .resources()
+        .writes()
+        .map(ToString::to_string)
+        .collect();
+    writes.extend(access.components().writes().map(ToString::to_string));
+
+    for (kind, mut ids) in [("reads", reads), ("writes", writes)] {
+        if !ids.is_empty() {
+            ids.sort();
+            label.push_str(&format!("\n{}: {}", kind, ids.join(", ")));
+        }
+    }
+    if access.is_exclusive() {
+        label.push_str("\nexclusive");
+    }
+    if access.is_thread_local() {
+        label.push_str("\nthread local");
+    }
+    label
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{query::Entities, Query, SystemBuilder};
@@ -192,4 +400,79 @@ mod tests {
 
         schedule.run(&mut world, &mut resources);
     }
+
+    #[test]
+    fn introspection() {
+        let mut schedule = Schedule::default();
+        schedule.add_stage("first", Stage::parallel());
+        schedule.add_stage_before("first", "startup", Stage::sequence_once());
+        schedule.add_system_to_stage("startup", startup());
+        schedule.add_system_to_stage("first", first1());
+        schedule.add_system_to_stage("first", first2());
+        schedule.add_system_to_stage("first", third());
+
+        let order: Vec<_> = schedule
+            .stages()
+            .map(|(label, stage)| (format!("{:?}", label), stage.executor_kind()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("\"startup\"".to_string(), ExecutorKind::SequenceOnce),
+                ("\"first\"".to_string(), ExecutorKind::Parallel),
+            ]
+        );
+
+        let first = schedule.stage("first").unwrap();
+        let systems: Vec<_> = first.systems().collect();
+        assert!(systems[0].name().contains("first1"));
+        assert_eq!(systems[0].access().components().writes().count(), 1);
+
+        let dot = schedule.to_dot();
+        assert!(dot.starts_with("digraph schedule {"));
+        assert!(dot.contains("label=\"\\\"startup\\\" (sequence once)\";"));
+        assert!(dot.contains("stage_0 -> stage_1"));
+        // first1 writes `Foo` that first2 and third read, first2 and third can
+        // run together.
+        assert!(dot.contains("s1_0 -> s1_1 [style=dashed, color=red];"));
+        assert!(dot.contains("s1_0 -> s1_2 [style=dashed, color=red];"));
+        assert!(!dot.contains("s1_1 -> s1_2"));
+    }
+
+    struct Renderer;
+    struct Settings;
+
+    #[test]
+    fn missing_resources() {
+        let mut resources = Resources::default();
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update", Stage::sequence());
+        schedule.add_system_to_stage(
+            "update",
+            SystemBuilder::new()
+                .name("render")
+                .read_resource::<Renderer>()
+                .write_resource::<Settings>()
+                .build(|_, _, _, _| {}),
+        );
+        schedule.add_system_to_stage(
+            "update",
+            SystemBuilder::new()
+                .try_read_resource::<Renderer>()
+                .build(|_, _, renderer, _| assert!(renderer.is_none())),
+        );
+
+        let err = schedule.validate(&resources).unwrap_err();
+        assert_eq!(err.missing().len(), 2);
+
+        resources.insert(Renderer);
+        resources.insert(Settings);
+        assert!(schedule.validate(&resources).is_ok());
+
+        resources.remove::<Renderer>();
+        let err = schedule.validate(&resources).unwrap_err();
+        assert_eq!(err.missing().len(), 1);
+        assert_eq!(err.missing()[0].1, ResourceTypeId::of::<Renderer>());
+        assert_eq!(err.missing()[0].2, "render");
+    }
 }
@@ -1,11 +1,19 @@
+#[cfg(feature = "diagnostics")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "diagnostics")]
+use crate::SystemTiming;
 use crate::{
-    Executor, ParRunnable, Resources, SequenceExecutor, SequenceOnceExecutor, SystemBox, World,
+    Executor, ExecutorKind, ParRunnable, ParallelExecutor, Resources, SequenceExecutor,
+    SequenceOnceExecutor, SystemBox, SystemErrorHandling, World,
 };
 
 pub struct Stage {
     executor: Box<dyn Executor>,
     systems: Vec<SystemBox>,
     modified: bool,
+    #[cfg(feature = "diagnostics")]
+    last_duration: Duration,
 }
 
 impl Stage {
@@ -14,6 +22,8 @@ impl Stage {
             executor: Box::new(executor),
             systems: Vec::new(),
             modified: false,
+            #[cfg(feature = "diagnostics")]
+            last_duration: Duration::default(),
         }
     }
 
@@ -25,24 +35,81 @@ impl Stage {
         Stage::new(SequenceOnceExecutor::default())
     }
 
+    pub fn parallel() -> Self {
+        Stage::new(ParallelExecutor::default())
+    }
+
     pub fn add_system<S: ParRunnable + 'static>(&mut self, system: S) -> &mut Self {
         self.modified = true;
         self.systems.push(SystemBox::new(system));
         self
     }
 
+    pub fn executor_kind(&self) -> ExecutorKind {
+        self.executor.kind()
+    }
+
+    /// Systems of the stage, in insertion order.
+    pub fn systems(&self) -> impl Iterator<Item = &dyn ParRunnable> {
+        self.systems.iter().map(|system| unsafe { system.get() })
+    }
+
     pub fn run(&mut self, world: &mut World, resources: &mut Resources) {
+        #[cfg(feature = "diagnostics")]
+        let start = Instant::now();
         if self.modified {
-            self.modified = true;
+            self.modified = false;
             self.executor.cache_data(&self.systems);
         }
-        self.executor
-            .run_systems(&self.systems, world, resources.internal());
-        self.systems.iter_mut().for_each(|system| {
-            let borrow = unsafe { system.get_mut() };
-            if let Some(cmd) = borrow.command_buffer_mut() {
-                cmd.flush(world);
+        let error_handling = resources
+            .get::<SystemErrorHandling>()
+            .map(|handling| *handling)
+            .unwrap_or_default();
+        for system in self.systems.iter_mut() {
+            system.catch_panics = error_handling.catch_panics;
+        }
+        {
+            let _span = tracing::info_span!("run_systems").entered();
+            self.executor
+                .run_systems(&self.systems, world, resources.internal());
+        }
+        {
+            let _span = tracing::info_span!("apply_commands").entered();
+            self.systems.iter_mut().for_each(|system| {
+                let borrow = unsafe { system.get_mut() };
+                if let Some(cmd) = borrow.command_buffer_mut() {
+                    cmd.flush(world);
+                }
+            });
+        }
+        for system in self.systems.iter_mut() {
+            if let Some(error) = system.take_error() {
+                error_handling.handle(error, resources);
             }
-        })
+        }
+        #[cfg(feature = "diagnostics")]
+        {
+            self.last_duration = start.elapsed();
+        }
+    }
+
+    /// Duration of the last run, command buffer flushes included.
+    #[cfg(feature = "diagnostics")]
+    pub fn last_duration(&self) -> Duration {
+        self.last_duration
+    }
+
+    /// Timings of the systems that ran since the last call.
+    #[cfg(feature = "diagnostics")]
+    pub(crate) fn take_system_timings(&self) -> Vec<SystemTiming> {
+        self.systems
+            .iter()
+            .filter_map(|system| {
+                system.take_duration().map(|duration| SystemTiming {
+                    name: unsafe { system.get() }.name().to_string(),
+                    duration,
+                })
+            })
+            .collect()
     }
 }
@@ -1,10 +1,11 @@
-use std::marker::PhantomData;
+use std::{any::type_name, borrow::Cow, marker::PhantomData};
 
 use util::cons::{ConsAppend, ConsFlatten};
 
 use crate::{
-    BoxedStageLabel, CommandBuffer, IntoView, Query, QuerySet, RawResources, Read, Resource,
-    ResourceSet, StageLabel, World, Write,
+    BoxedError, BoxedStageLabel, CommandBuffer, IntoSystemResult, IntoView, NonSend, NonSendMut,
+    Query, QuerySet, RawResources, Read, Resource, ResourceSet, StageLabel, SystemAccess,
+    SystemTicks, TryRead, TryWrite, World, Write,
 };
 
 use super::executor::Runnable;
@@ -20,12 +21,12 @@ where
         commands: &mut CommandBuffer,
         resources: &mut R::Item,
         queries: &mut Q,
-    );
+    ) -> Result<(), BoxedError>;
 }
 
 impl<F, R, Q> SystemFn<R, Q> for F
 where
-    F: FnMut(&World, &mut CommandBuffer, &mut R::Item, &mut Q),
+    F: FnMut(&World, &mut CommandBuffer, &mut R::Item, &mut Q) -> Result<(), BoxedError>,
     R: ResourceSet<'static>,
     Q: QuerySet,
 {
@@ -35,8 +36,8 @@ where
         commands: &mut CommandBuffer,
         resources: &mut R::Item,
         queries: &mut Q,
-    ) {
-        (self)(world, commands, resources, queries);
+    ) -> Result<(), BoxedError> {
+        (self)(world, commands, resources, queries)
     }
 }
 
@@ -47,7 +48,10 @@ unsafe impl<T: Sync> Sync for ResourceMarker<T> {}
 pub struct System<R, Q, F> {
     _resources: ResourceMarker<R>,
     queries: Q,
+    name: Cow<'static, str>,
     stage: Option<BoxedStageLabel>,
+    access: SystemAccess,
+    last_run: u64,
     run_fn: F,
     command_buffer: Option<CommandBuffer>,
 }
@@ -66,22 +70,42 @@ where
         self.stage.clone()
     }
 
-    unsafe fn run_unsafe(&mut self, world: &World, resources: &RawResources) {
+    fn access(&self) -> &SystemAccess {
+        &self.access
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    unsafe fn run_unsafe(
+        &mut self,
+        world: &World,
+        resources: &RawResources,
+    ) -> Result<(), BoxedError> {
+        let ticks = SystemTicks {
+            last_run: self.last_run,
+            this_run: resources.increment_change_tick(),
+        };
         let resources_static = &*(resources as *const RawResources);
-        let mut resources = R::fetch(resources_static);
+        let mut resources = R::fetch(resources_static, ticks);
 
         let queries = &mut self.queries;
         let command = self.command_buffer.get_or_insert(CommandBuffer::new());
 
         let borrow_fn = &mut self.run_fn;
-        borrow_fn.run(world, command, &mut resources, queries);
+        let result = borrow_fn.run(world, command, &mut resources, queries);
+        self.last_run = ticks.this_run;
+        result
     }
 }
 
 pub struct SystemBuilder<R = (), Q = ()> {
     queries: Q,
     resources: R,
+    name: Option<Cow<'static, str>>,
     stage: Option<BoxedStageLabel>,
+    exclusive: bool,
 }
 
 impl SystemBuilder<(), ()> {
@@ -89,7 +113,9 @@ impl SystemBuilder<(), ()> {
         SystemBuilder {
             queries: (),
             resources: (),
+            name: None,
             stage: None,
+            exclusive: false,
         }
     }
 }
@@ -106,33 +132,121 @@ where
         SystemBuilder {
             queries: self.queries,
             resources: self.resources,
+            name: self.name,
             stage: Some(label.dyn_clone()),
+            exclusive: self.exclusive,
         }
     }
 
+    /// Name shown in errors, panics and diagnostics, the type name of the
+    /// system function by default.
+    pub fn name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Never run the system alongside another one, for systems that reach
+    /// components without a query.
+    pub fn exclusive(mut self) -> Self {
+        self.exclusive = true;
+        self
+    }
+
     pub fn read_resource<T>(self) -> SystemBuilder<<R as ConsAppend<Read<T>>>::Output, Q>
     where
-        T: 'static + Resource,
+        T: 'static + Resource + Sync,
         R: ConsAppend<Read<T>>,
         <R as ConsAppend<Read<T>>>::Output: ConsFlatten,
     {
         SystemBuilder {
             queries: self.queries,
             resources: ConsAppend::append(self.resources, Read::<T>::default()),
+            name: self.name,
             stage: self.stage,
+            exclusive: self.exclusive,
         }
     }
 
     pub fn write_resource<T>(self) -> SystemBuilder<<R as ConsAppend<Write<T>>>::Output, Q>
     where
-        T: 'static + Resource,
+        T: 'static + Resource + Send,
         R: ConsAppend<Write<T>>,
         <R as ConsAppend<Write<T>>>::Output: ConsFlatten,
     {
         SystemBuilder {
             queries: self.queries,
             resources: ConsAppend::append(self.resources, Write::<T>::default()),
+            name: self.name,
+            stage: self.stage,
+            exclusive: self.exclusive,
+        }
+    }
+
+    /// Read a resource that may not exist, the system receives `None` instead of panicking.
+    pub fn try_read_resource<T>(self) -> SystemBuilder<<R as ConsAppend<TryRead<T>>>::Output, Q>
+    where
+        T: 'static + Resource + Sync,
+        R: ConsAppend<TryRead<T>>,
+        <R as ConsAppend<TryRead<T>>>::Output: ConsFlatten,
+    {
+        SystemBuilder {
+            queries: self.queries,
+            resources: ConsAppend::append(self.resources, TryRead::<T>::default()),
+            name: self.name,
+            stage: self.stage,
+            exclusive: self.exclusive,
+        }
+    }
+
+    /// Write a resource that may not exist, the system receives `None` instead of panicking.
+    pub fn try_write_resource<T>(self) -> SystemBuilder<<R as ConsAppend<TryWrite<T>>>::Output, Q>
+    where
+        T: 'static + Resource + Send,
+        R: ConsAppend<TryWrite<T>>,
+        <R as ConsAppend<TryWrite<T>>>::Output: ConsFlatten,
+    {
+        SystemBuilder {
+            queries: self.queries,
+            resources: ConsAppend::append(self.resources, TryWrite::<T>::default()),
+            name: self.name,
+            stage: self.stage,
+            exclusive: self.exclusive,
+        }
+    }
+
+    /// Read a resource that is not `Sync`, the system will always run on the main thread.
+    pub fn read_non_send_resource<T>(
+        self,
+    ) -> SystemBuilder<<R as ConsAppend<NonSend<T>>>::Output, Q>
+    where
+        T: 'static + Resource,
+        R: ConsAppend<NonSend<T>>,
+        <R as ConsAppend<NonSend<T>>>::Output: ConsFlatten,
+    {
+        SystemBuilder {
+            queries: self.queries,
+            resources: ConsAppend::append(self.resources, NonSend::<T>::default()),
+            name: self.name,
             stage: self.stage,
+            exclusive: self.exclusive,
+        }
+    }
+
+    /// Write a resource that is not `Send`, the system will always run on the main thread.
+    pub fn write_non_send_resource<T>(
+        self,
+    ) -> SystemBuilder<<R as ConsAppend<NonSendMut<T>>>::Output, Q>
+    where
+        T: 'static + Resource,
+        R: ConsAppend<NonSendMut<T>>,
+        <R as ConsAppend<NonSendMut<T>>>::Output: ConsFlatten,
+    {
+        SystemBuilder {
+            queries: self.queries,
+            resources: ConsAppend::append(self.resources, NonSendMut::<T>::default()),
+            name: self.name,
+            stage: self.stage,
+            exclusive: self.exclusive,
         }
     }
 
@@ -147,29 +261,55 @@ where
         SystemBuilder {
             queries: ConsAppend::append(self.queries, query),
             resources: self.resources,
+            name: self.name,
             stage: self.stage,
+            exclusive: self.exclusive,
         }
     }
 
-    pub fn build<F>(
+    /// Build the system from a function returning `()` or a `Result`, errors
+    /// are handled by the stage as configured by `SystemErrorHandling`.
+    pub fn build<F, O>(
         self,
-        run_fn: F,
-    ) -> System<<R as ConsFlatten>::Output, <Q as ConsFlatten>::Output, F>
+        mut run_fn: F,
+    ) -> System<
+        <R as ConsFlatten>::Output,
+        <Q as ConsFlatten>::Output,
+        impl SystemFn<<R as ConsFlatten>::Output, <Q as ConsFlatten>::Output>,
+    >
     where
         F: FnMut(
             &World,
             &mut CommandBuffer,
             &mut <<R as ConsFlatten>::Output as ResourceSet<'static>>::Item,
             &mut <Q as ConsFlatten>::Output,
-        ),
+        ) -> O,
+        O: IntoSystemResult,
         <R as ConsFlatten>::Output: for<'a> ResourceSet<'a>,
         <Q as ConsFlatten>::Output: QuerySet,
     {
+        let mut access = SystemAccess::default();
+        <<R as ConsFlatten>::Output as ResourceSet>::access(&mut access);
+        <<Q as ConsFlatten>::Output as QuerySet>::access(access.components_mut());
+        if self.exclusive {
+            access.set_exclusive();
+        }
+
         System {
             _resources: ResourceMarker(PhantomData),
             queries: self.queries.flatten(),
+            name: self.name.unwrap_or_else(|| type_name::<F>().into()),
             stage: self.stage,
-            run_fn,
+            access,
+            last_run: 0,
+            run_fn: move |world: &World,
+                          commands: &mut CommandBuffer,
+                          resources: &mut <<R as ConsFlatten>::Output as ResourceSet<
+                'static,
+            >>::Item,
+                          queries: &mut <Q as ConsFlatten>::Output| {
+                run_fn(world, commands, resources, queries).into_system_result()
+            },
             command_buffer: None,
         }
     }
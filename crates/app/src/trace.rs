@@ -0,0 +1,116 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::{filter::filter_fn, layer::SubscriberExt, Layer, Registry};
+
+use crate::{App, AppStage, ParRunnable, Plugin, SystemBuilder};
+
+/// Write the schedule, stage, system and command buffer flush spans of the
+/// first `frames` frames to a Chrome trace JSON file, viewable in
+/// `chrome://tracing` or Perfetto. Every thread a system ran on gets its own
+/// track.
+///
+/// The plugin installs the global `tracing` subscriber, it does nothing if one
+/// is already set.
+pub struct ChromeTracePlugin {
+    pub path: PathBuf,
+    pub frames: u32,
+}
+
+impl Default for ChromeTracePlugin {
+    fn default() -> Self {
+        ChromeTracePlugin {
+            path: PathBuf::from("trace.json"),
+            frames: 60,
+        }
+    }
+}
+
+impl Plugin for ChromeTracePlugin {
+    fn build(&mut self, app: &mut App) {
+        let recording = Arc::new(AtomicBool::new(true));
+        let (layer, guard) = ChromeLayerBuilder::new()
+            .file(&self.path)
+            .include_args(true)
+            .build();
+        let filter = {
+            let recording = recording.clone();
+            filter_fn(move |_| recording.load(Ordering::Relaxed))
+        };
+        let subscriber = Registry::default().with(layer.with_filter(filter));
+        if tracing::subscriber::set_global_default(subscriber).is_err() {
+            tracing::warn!("a tracing subscriber is already set, no Chrome trace will be written");
+            return;
+        }
+
+        app.add_resource(ChromeTrace {
+            path: self.path.clone(),
+            frames_left: self.frames,
+            recording,
+            guard: Some(guard),
+        })
+        .add_system(chrome_trace_sys());
+    }
+}
+
+struct ChromeTrace {
+    path: PathBuf,
+    frames_left: u32,
+    recording: Arc<AtomicBool>,
+    guard: Option<FlushGuard>,
+}
+
+fn chrome_trace_sys() -> impl ParRunnable {
+    SystemBuilder::new()
+        .on_stage(AppStage::End)
+        .write_resource::<ChromeTrace>()
+        .build(|_, _, trace, _| {
+            if trace.frames_left > 0 {
+                trace.frames_left -= 1;
+                if trace.frames_left == 0 {
+                    trace.recording.store(false, Ordering::Relaxed);
+                }
+            } else if let Some(guard) = trace.guard.take() {
+                // Dropped a frame late, so the spans still open when recording
+                // stopped are closed in the file.
+                drop(guard);
+                tracing::info!(path = %trace.path.display(), "Chrome trace written");
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Foo;
+
+    #[test]
+    fn chrome_trace() {
+        let path = std::env::temp_dir().join(format!("chrome_trace_{}.json", std::process::id()));
+        let mut app = App::new();
+        app.add_plugin(ChromeTracePlugin {
+            path: path.clone(),
+            frames: 2,
+        })
+        .add_system(SystemBuilder::new().build(|world, cmd, _, _| {
+            cmd.spawn(world).add(Foo);
+        }));
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let trace = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(trace.contains("\"name\":\"schedule\""));
+        assert!(trace.contains("\"name\":\"system\""));
+        assert!(trace.contains("\"name\":\"flush\""));
+        assert!(trace.contains("trace::tests"));
+    }
+}
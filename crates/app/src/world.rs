@@ -1,8 +1,22 @@
 use crate::{
     entity::{Entity, EntityAllocator},
-    Component, Components,
+    Component, ComponentDescriptor, ComponentTypeId, Components,
 };
 
+/// Copy of a set of component types and of the entity allocator, taken with
+/// `World::snapshot`.
+pub struct WorldSnapshot {
+    type_ids: Vec<ComponentTypeId>,
+    components: Components,
+    entity_allocator: EntityAllocator,
+}
+
+impl WorldSnapshot {
+    pub fn type_ids(&self) -> &[ComponentTypeId] {
+        &self.type_ids
+    }
+}
+
 #[derive(Default)]
 pub struct World {
     components: Components,
@@ -66,6 +80,194 @@ impl World {
         }
     }
 
+    /// Register a component type defined at runtime, returning its id.
+    pub fn register_dynamic_component(
+        &mut self,
+        descriptor: ComponentDescriptor,
+    ) -> ComponentTypeId {
+        self.components.register_dynamic(descriptor)
+    }
+
+    pub fn dynamic_component_id(&self, name: &str) -> Option<ComponentTypeId> {
+        self.components.dynamic_id(name)
+    }
+
+    /// Add a component by copying the bytes behind `component`, which the world
+    /// then owns and drops. Nothing happens if `entity` is not alive.
+    ///
+    /// # Safety
+    /// `component` must point to a valid value of the `type_id` layout, that is
+    /// not dropped by the caller afterwards.
+    ///
+    /// # Panics
+    /// If `type_id` has no storage in this world.
+    pub unsafe fn add_component_raw(
+        &mut self,
+        entity: Entity,
+        type_id: ComponentTypeId,
+        component: *mut u8,
+    ) {
+        if self.entity_allocator.is_live(entity) {
+            if !self.components.insert_raw(&type_id, entity, component) {
+                panic!("Component '{}' is not registered", type_id);
+            }
+            self.entity_allocator.add_component_raw(entity, type_id);
+        }
+    }
+
+    pub fn remove_component_raw(&mut self, entity: Entity, type_id: ComponentTypeId) {
+        if self.entity_allocator.is_live(entity) {
+            self.components.remove_raw(&type_id, entity);
+            self.entity_allocator.remove_component_raw(entity, &type_id);
+        }
+    }
+
+    pub fn is_live(&self, entity: Entity) -> bool {
+        self.entity_allocator.is_live(entity)
+    }
+
+    /// Number of live entities.
+    pub fn len(&self) -> u32 {
+        self.entity_allocator.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of stored components of every component type.
+    pub fn component_counts(&self) -> Vec<(ComponentTypeId, usize)> {
+        self.components.storage_sizes()
+    }
+
+    /// Every live entity of the world.
+    pub fn entities(&self) -> Vec<Entity> {
+        self.entity_allocator
+            .get_bitset()
+            .iter()
+            .filter_map(|id| self.entity_allocator.get_entity(id as u32))
+            .collect()
+    }
+
+    pub fn entity_component_types(&self, entity: Entity) -> Vec<ComponentTypeId> {
+        self.entity_allocator
+            .components_of(entity)
+            .map(|components| components.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Type erased pointer to a component, dereferencing it is only sound while
+    /// no system borrows the same component.
+    pub fn component_ptr(&self, type_id: &ComponentTypeId, entity: Entity) -> Option<*mut u8> {
+        self.components.get_ptr_raw(type_id, entity)
+    }
+
+    /// Move `entity` and all of its components into `other`, returning the entity
+    /// allocated there.
+    pub fn move_entity_to(&mut self, entity: Entity, other: &mut World) -> Option<Entity> {
+        if !self.entity_allocator.is_live(entity) {
+            return None;
+        }
+
+        let components = self.entity_allocator.delloc(entity).unwrap_or_default();
+        let new_entity = other.entity_allocator.alloc();
+        for type_id in components {
+            if self
+                .components
+                .move_raw(&type_id, entity, &mut other.components, new_entity)
+            {
+                other
+                    .entity_allocator
+                    .add_component_raw(new_entity, type_id);
+            }
+        }
+        Some(new_entity)
+    }
+
+    /// Register how to clone `T`, needed by prefabs and snapshots.
+    pub fn register_clone<T: Component + Clone>(&mut self) {
+        self.components.register_clone::<T>();
+    }
+
+    /// Clone every component of `entity` onto `target` in `other`, replacing the
+    /// components `target` already has.
+    ///
+    /// # Panics
+    /// If a component type has no registered clone function.
+    pub fn clone_entity_into(&self, entity: Entity, other: &mut World, target: Entity) {
+        if !other.entity_allocator.is_live(target) {
+            return;
+        }
+        for type_id in self.entity_component_types(entity) {
+            if !self
+                .components
+                .clone_raw(&type_id, entity, &mut other.components, target)
+            {
+                panic!("Component '{}' has no registered clone function", type_id);
+            }
+            other.entity_allocator.add_component_raw(target, type_id);
+        }
+    }
+
+    /// Clone the components of `type_ids` and the entity allocator state.
+    ///
+    /// # Panics
+    /// If a component type has no registered clone function.
+    pub fn snapshot(&self, type_ids: &[ComponentTypeId]) -> WorldSnapshot {
+        let mut components = Components::default();
+        for type_id in type_ids {
+            if self.components.clone_fn(type_id).is_none() {
+                panic!("Component '{}' has no registered clone function", type_id);
+            }
+            for entity in self.entity_allocator.live_entities() {
+                self.components
+                    .clone_raw(type_id, entity, &mut components, entity);
+            }
+        }
+
+        let mut entity_allocator = self.entity_allocator.clone();
+        entity_allocator.retain_components(|type_id| type_ids.contains(type_id));
+        WorldSnapshot {
+            type_ids: type_ids.to_vec(),
+            components,
+            entity_allocator,
+        }
+    }
+
+    /// Roll the world back to `snapshot`. Entity ids and generations are the
+    /// same as when the snapshot was taken. Components outside the snapshot are
+    /// kept on entities alive in both states and dropped from the others.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) {
+        let current = std::mem::replace(
+            &mut self.entity_allocator,
+            snapshot.entity_allocator.clone(),
+        );
+        for entity in current.live_entities() {
+            let survives = self.entity_allocator.is_live(entity);
+            for type_id in current.components_of(entity).into_iter().flatten() {
+                if snapshot.type_ids.contains(type_id) {
+                    continue;
+                }
+                if survives {
+                    self.entity_allocator.add_component_raw(entity, *type_id);
+                } else {
+                    self.components.remove_raw(type_id, entity);
+                }
+            }
+        }
+
+        for type_id in snapshot.type_ids.iter() {
+            self.components.clear_raw(type_id);
+        }
+        for entity in self.entity_allocator.live_entities() {
+            for type_id in snapshot.type_ids.iter() {
+                snapshot
+                    .components
+                    .clone_raw(type_id, entity, &mut self.components, entity);
+            }
+        }
+    }
+
     pub(crate) fn reserve_entity(&self) -> Entity {
         self.entity_allocator.reserve()
     }
@@ -82,3 +284,85 @@ impl World {
         &self.entity_allocator
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Entities, Query};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Foo(i32);
+    #[derive(Debug, PartialEq)]
+    struct Bar(String);
+
+    #[test]
+    fn move_entity_to() {
+        let mut main = World::default();
+        let mut other = World::default();
+        other.spawn().add(Foo(-1));
+
+        let a = main.spawn().add(Foo(0)).add(Bar("a".to_string())).entity();
+        let b = main.spawn().add(Foo(1)).entity();
+
+        let moved = main.move_entity_to(a, &mut other).unwrap();
+        assert!(!main.is_live(a));
+        assert!(other.is_live(moved));
+        assert!(main.move_entity_to(a, &mut other).is_none());
+
+        let query = Query::<(Entities, &Foo)>::new();
+        let main_items = query.iter(&main);
+        assert_eq!(main_items.len(), 1);
+        assert_eq!(main_items[0].0.id(), b.id());
+
+        let query = Query::<(Entities, &Foo, &Bar)>::new();
+        let other_items = query.iter(&other);
+        assert_eq!(other_items.len(), 1);
+        assert_eq!(other_items[0].0.id(), moved.id());
+        assert_eq!(other_items[0].1, &Foo(0));
+        assert_eq!(other_items[0].2, &Bar("a".to_string()));
+
+        other.despawn(moved);
+        assert!(Query::<&Bar>::new().iter(&other).is_empty());
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Position(i32);
+
+    #[test]
+    fn snapshot_restore() {
+        let mut world = World::default();
+        world.register_clone::<Position>();
+        let a = world.spawn().add(Position(0)).add(Foo(0)).entity();
+        let b = world.spawn().add(Position(1)).add(Foo(1)).entity();
+        let c = world.spawn().add(Position(2)).entity();
+        world.despawn(c);
+
+        let snapshot = world.snapshot(&[ComponentTypeId::of::<Position>()]);
+
+        let d = world.spawn().add(Position(3)).add(Foo(3)).entity();
+        assert_eq!(d.id(), c.id());
+        world.despawn(b);
+        world.add_component(a, Position(10));
+        world.add_component(a, Foo(10));
+
+        world.restore(&snapshot);
+        assert!(world.is_live(a) && world.is_live(b));
+        assert!(!world.is_live(d));
+
+        let query = Query::<(Entities, &Position)>::new();
+        assert_eq!(
+            query.iter(&world),
+            vec![(a, &Position(0)), (b, &Position(1))]
+        );
+        let query = Query::<(Entities, &Foo)>::new();
+        assert_eq!(query.iter(&world), vec![(a, &Foo(10))]);
+        assert_eq!(
+            world.entity_component_types(b),
+            vec![ComponentTypeId::of::<Position>()]
+        );
+
+        // Entities spawned after the rollback get the same ids as before it.
+        assert_eq!(world.spawn().entity(), d);
+    }
+}
@@ -0,0 +1,220 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Index, Member};
+
+struct ReflectField {
+    member: Member,
+    name: String,
+    ty: syn::Type,
+    ignored: bool,
+}
+
+fn is_ignored(field: &syn::Field) -> bool {
+    field.attrs.iter().any(|attr| {
+        attr.path.is_ident("reflect")
+            && attr
+                .parse_args::<syn::Ident>()
+                .map(|ident| ident == "ignore")
+                .unwrap_or(false)
+    })
+}
+
+/// Path of the `app` crate, `#[reflect(crate = "path")]` on the struct when
+/// the crate is renamed or from inside `app` itself.
+fn crate_path(input: &DeriveInput) -> syn::Result<syn::Path> {
+    match input
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("reflect"))
+    {
+        Some(attr) => attr
+            .parse_args_with(|input: syn::parse::ParseStream| {
+                input.parse::<syn::Token![crate]>()?;
+                input.parse::<syn::Token![=]>()?;
+                input.parse::<syn::LitStr>()
+            })?
+            .parse(),
+        None => Ok(parse_quote!(::app)),
+    }
+}
+
+/// Derive `app::Reflect` for a struct, exposing every field not marked with
+/// `#[reflect(ignore)]`. Tuple struct fields are named by their index, type
+/// parameters must implement `Reflect` and ignored fields `Default`.
+#[proc_macro_derive(Reflect, attributes(reflect))]
+pub fn derive_reflect(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive_reflect_inner(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn derive_reflect_inner(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let ident_name = ident.to_string();
+    let krate = crate_path(&input)?;
+    let mut generics = input.generics.clone();
+    for param in input.generics.type_params() {
+        let param = &param.ident;
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#param: #krate::Reflect));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let (fields, ignored): (Vec<_>, Vec<_>) = match &input.data {
+        Data::Struct(data) => data
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let (name, member) = match &field.ident {
+                    Some(ident) => (ident.to_string(), Member::Named(ident.clone())),
+                    None => (index.to_string(), Member::Unnamed(Index::from(index))),
+                };
+                ReflectField {
+                    member,
+                    name,
+                    ty: field.ty.clone(),
+                    ignored: is_ignored(field),
+                }
+            })
+            .partition(|field| !field.ignored),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Reflect can only be derived for structs",
+            ))
+        }
+    };
+
+    let ignored = ignored
+        .iter()
+        .map(|field| &field.member)
+        .collect::<Vec<_>>();
+    let names = fields.iter().map(|field| &field.name).collect::<Vec<_>>();
+    let members = fields.iter().map(|field| &field.member).collect::<Vec<_>>();
+    let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+
+    let (unit_arm, from_unit_arm) = if fields.is_empty() {
+        (
+            quote!(#krate::Value::Unit => Ok(()),),
+            quote!(#krate::Value::Unit => Ok(Self {
+                #(#ignored: ::std::default::Default::default(),)*
+            }),),
+        )
+    } else {
+        (quote!(), quote!())
+    };
+    let to_value = if fields.is_empty() {
+        quote!(#krate::Value::Unit)
+    } else {
+        quote! {
+            #krate::Value::Struct(vec![
+                #((#names.to_string(), #krate::Reflect::to_value(&self.#members)),)*
+            ])
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics #krate::Reflect for #ident #ty_generics #where_clause {
+            fn type_name(&self) -> &'static str {
+                ::std::any::type_name::<Self>()
+            }
+
+            fn type_info() -> #krate::TypeInfo
+            where
+                Self: Sized,
+            {
+                #krate::TypeInfo {
+                    type_name: ::std::any::type_name::<Self>(),
+                    fields: vec![#(#krate::FieldInfo::of::<#types>(#names),)*],
+                }
+            }
+
+            fn as_any(&self) -> &dyn ::std::any::Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn ::std::any::Any {
+                self
+            }
+
+            fn as_reflect(&self) -> &dyn #krate::Reflect {
+                self
+            }
+
+            fn as_reflect_mut(&mut self) -> &mut dyn #krate::Reflect {
+                self
+            }
+
+            fn fields(&self) -> Vec<(&'static str, &dyn #krate::Reflect)> {
+                vec![#((#names, &self.#members as &dyn #krate::Reflect),)*]
+            }
+
+            fn field(&self, name: &str) -> Option<&dyn #krate::Reflect> {
+                match name {
+                    #(#names => Some(&self.#members),)*
+                    _ => None,
+                }
+            }
+
+            fn field_mut(&mut self, name: &str) -> Option<&mut dyn #krate::Reflect> {
+                match name {
+                    #(#names => Some(&mut self.#members),)*
+                    _ => None,
+                }
+            }
+
+            fn to_value(&self) -> #krate::Value {
+                #to_value
+            }
+
+            fn from_value(value: &#krate::Value) -> Result<Self, #krate::ReflectError>
+            where
+                Self: Sized,
+            {
+                match value {
+                    #from_unit_arm
+                    #krate::Value::Struct(_) | #krate::Value::List(_) => Ok(Self {
+                        #(#members: #krate::Reflect::from_value(value.field(#names).ok_or_else(
+                            || #krate::ReflectError::missing_field::<Self>(#names),
+                        )?)?,)*
+                        #(#ignored: ::std::default::Default::default(),)*
+                    }),
+                    _ => Err(#krate::ReflectError::mismatch::<Self>(value)),
+                }
+            }
+
+            fn apply(&mut self, value: &#krate::Value) -> Result<(), #krate::ReflectError> {
+                match value {
+                    #unit_arm
+                    #krate::Value::Struct(_) | #krate::Value::List(_) => {
+                        for (name, value) in value.fields() {
+                            match name.as_ref() {
+                                #(#names => #krate::Reflect::apply(&mut self.#members, value)?,)*
+                                _ => {
+                                    return Err(#krate::ReflectError::UnknownField {
+                                        type_name: ::std::any::type_name::<Self>(),
+                                        field: name.into_owned(),
+                                    })
+                                }
+                            }
+                        }
+                        Ok(())
+                    }
+                    _ => Err(#krate::ReflectError::mismatch::<Self>(value)),
+                }
+            }
+
+            fn debug(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(#ident_name)
+                    #(.field(#names, &(&self.#members as &dyn #krate::Reflect)))*
+                    .finish()
+            }
+        }
+    })
+}
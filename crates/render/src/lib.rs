@@ -6,8 +6,11 @@ pub mod renderer;
 mod surface;
 mod texture;
 
+use std::any::type_name;
+
 use app::{AppStage, Plugin, Stage, StageLabel};
 use util::pollster;
+use window_plugin::WindowPlugin;
 
 pub use renderer::*;
 pub use wgpu;
@@ -52,4 +55,8 @@ impl Plugin for RenderPlugin {
             .add_system(handle_window_closed_sys())
             .add_system(update_renderer_sys());
     }
+
+    fn dependencies(&self) -> Vec<&'static str> {
+        vec![type_name::<WindowPlugin>()]
+    }
 }
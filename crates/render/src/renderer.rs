@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use app::{EventReader, Events, ParRunnable, SystemBuilder};
 use window_plugin::{
     winit::window::{Window, WindowId},
-    WindowClosed, WindowCreated, WindowManager, WindowResized,
+    PresentMode, WindowClosed, WindowCreated, WindowManager, WindowResized,
 };
 
 use crate::{renderable::Renderable, surface::SurfaceInfo, RenderStage};
@@ -27,6 +27,7 @@ impl Renderer {
             .request_adapter(&wgpu::RequestAdapterOptions {
                 power_preference: wgpu::PowerPreference::default(),
                 compatible_surface: None,
+                force_fallback_adapter: false,
             })
             .await
             .unwrap();
@@ -56,13 +57,13 @@ impl Renderer {
         }
     }
 
-    pub fn create_surface(&mut self, window: &Window) {
+    pub fn create_surface(&mut self, window: &Window, present_mode: PresentMode) {
         let window_id = window.id();
         if self.surface_infos.contains_key(&window_id) {
             panic!("Duplicated create surface for a window",);
         }
 
-        let surface_info = SurfaceInfo::new(window, &self.instance, &self.device);
+        let surface_info = SurfaceInfo::new(window, present_mode, &self.instance, &self.device);
         self.surface_infos.insert(window_id, surface_info);
     }
 
@@ -108,13 +109,16 @@ pub(crate) fn handle_window_created_sys() -> impl ParRunnable {
         .on_stage(RenderStage::PostRender)
         .write_resource::<Renderer>()
         .read_resource::<Events<WindowCreated>>()
-        .read_resource::<WindowManager>()
+        .read_non_send_resource::<WindowManager>()
         .build(move |_, _, (renderer, events, window_manager), _| {
             for event in event_reader.iter(events) {
                 let window = window_manager
                     .get(&event.id)
                     .expect("Created window event but window not found.");
-                renderer.create_surface(window);
+                let present_mode = window_manager
+                    .descriptor(&event.id)
+                    .map_or(PresentMode::Fifo, |descriptor| descriptor.present_mode);
+                renderer.create_surface(window, present_mode);
             }
         })
 }
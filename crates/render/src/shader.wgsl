@@ -1,10 +1,8 @@
 // Vertex
-[[block]]
 struct CameraUniform {
   view: mat4x4<f32>;
 };
 
-[[block]]
 struct ProjectionUniform {
   view: mat4x4<f32>;
 };
@@ -25,7 +23,7 @@ struct VertexOutput {
 };
 
 [[stage(vertex)]]
-fn main(
+fn vs_main(
   model: VertexInput,
 ) -> VertexOutput {
   var out: VertexOutput;
@@ -41,6 +39,6 @@ var t_diffuse: texture_2d<f32>;
 var s_diffuse: sampler;
 
 [[stage(fragment)]]
-fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
   return textureSample(t_diffuse, s_diffuse, in.tex_coords);
 }
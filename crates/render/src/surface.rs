@@ -1,6 +1,6 @@
 use util::cgmath;
 use wgpu::util::DeviceExt;
-use window_plugin::winit::window::Window;
+use window_plugin::{winit::window::Window, PresentMode};
 
 use crate::{
     camera::{camera_projection_layout, Camera, PerspectiveProjection},
@@ -18,7 +18,12 @@ pub struct SurfaceInfo {
 }
 
 impl SurfaceInfo {
-    pub fn new(window: &Window, instance: &wgpu::Instance, device: &wgpu::Device) -> Self {
+    pub fn new(
+        window: &Window,
+        present_mode: PresentMode,
+        instance: &wgpu::Instance,
+        device: &wgpu::Device,
+    ) -> Self {
         let size = window.inner_size();
 
         let surface = unsafe { instance.create_surface(window) };
@@ -27,7 +32,11 @@ impl SurfaceInfo {
             format: wgpu::TextureFormat::Bgra8UnormSrgb,
             width: size.width,
             height: size.height,
-            present_mode: wgpu::PresentMode::Immediate,
+            present_mode: match present_mode {
+                PresentMode::Immediate => wgpu::PresentMode::Immediate,
+                PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+                PresentMode::Fifo => wgpu::PresentMode::Fifo,
+            },
         };
         surface.configure(&device, &config);
 
@@ -101,7 +110,7 @@ impl SurfaceInfo {
         queue: &wgpu::Queue,
         renderable: &Renderable,
     ) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_frame()?.output;
+        let output = self.surface.get_current_texture()?;
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
@@ -131,6 +140,7 @@ impl SurfaceInfo {
         }
 
         queue.submit(std::iter::once(encoder.finish()));
+        output.present();
         Ok(())
     }
 }
@@ -0,0 +1,273 @@
+use std::sync::Arc;
+
+use app::{
+    CommandBuffer, ComponentRegistration, ComponentTypeId, DynamicQuery, Entity, TypeRegistry,
+    World, WorldWritable,
+};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, INT};
+use util::parking_lot::Mutex;
+
+use crate::{
+    value::{from_dynamic, to_dynamic},
+    ScriptEvents,
+};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Handle passed to script systems. Rhai values must be `Clone`, so the data
+/// lives behind a shared slot that is emptied once the systems returned, a
+/// copy kept by a script then only gets errors.
+#[derive(Clone)]
+pub(crate) struct ScriptContext {
+    data: Arc<Mutex<Option<ContextData>>>,
+}
+
+impl ScriptContext {
+    pub(crate) fn new(
+        world: &World,
+        commands: &mut CommandBuffer,
+        registry: &TypeRegistry,
+        events: &mut ScriptEvents,
+    ) -> Self {
+        ScriptContext {
+            data: Arc::new(Mutex::new(Some(ContextData {
+                world,
+                commands,
+                registry,
+                events,
+            }))),
+        }
+    }
+
+    /// Drop the borrowed data, every copy of the context stops working.
+    pub(crate) fn close(&self) {
+        self.data.lock().take();
+    }
+
+    fn with<T>(&mut self, f: impl FnOnce(&mut ContextData) -> ScriptResult<T>) -> ScriptResult<T> {
+        match self.data.lock().as_mut() {
+            Some(data) => f(data),
+            None => Err("the context is only valid while its system runs".into()),
+        }
+    }
+}
+
+struct ContextData {
+    world: *const World,
+    commands: *mut CommandBuffer,
+    registry: *const TypeRegistry,
+    events: *mut ScriptEvents,
+}
+
+// Only reached through `ScriptContext` until it is closed, while the borrows
+// the pointers come from are alive.
+unsafe impl Send for ContextData {}
+
+impl ContextData {
+    fn world(&self) -> &World {
+        unsafe { &*self.world }
+    }
+
+    fn commands(&mut self) -> &mut CommandBuffer {
+        unsafe { &mut *self.commands }
+    }
+
+    fn registry(&self) -> &TypeRegistry {
+        unsafe { &*self.registry }
+    }
+
+    fn events(&mut self) -> &mut ScriptEvents {
+        unsafe { &mut *self.events }
+    }
+
+    fn registration(&self, name: &str) -> ScriptResult<&ComponentRegistration> {
+        self.registry()
+            .get_by_name(name)
+            .ok_or_else(|| format!("component '{}' is not registered", name).into())
+    }
+
+    fn query(&mut self, names: Array) -> ScriptResult<Array> {
+        let mut query = DynamicQuery::new();
+        for name in names {
+            let name = name.into_string()?;
+            query = query.read(self.registration(&name)?.type_id());
+        }
+        Ok(query
+            .iter(self.world())
+            .into_iter()
+            .map(|item| Dynamic::from(item.entity))
+            .collect())
+    }
+
+    /// Split `Component.field.path` into the component id and the field path.
+    fn component_path<'a>(
+        &self,
+        entity: Entity,
+        path: &'a str,
+    ) -> ScriptResult<(ComponentTypeId, *mut u8, &'a str)> {
+        let (name, field_path) = match path.find('.') {
+            Some(index) => (&path[..index], &path[index + 1..]),
+            None => (path, ""),
+        };
+        let type_id = self.registration(name)?.type_id();
+        let ptr = self
+            .world()
+            .component_ptr(&type_id, entity)
+            .ok_or_else(|| format!("entity {:?} has no component '{}'", entity, name))?;
+        Ok((type_id, ptr, field_path))
+    }
+
+    fn get(&mut self, entity: Entity, path: &str) -> ScriptResult<Dynamic> {
+        let (type_id, ptr, field_path) = self.component_path(entity, path)?;
+        let value = unsafe { self.registry().get_path(&type_id, ptr, field_path) }
+            .map_err(|err| err.to_string())?
+            .to_value();
+        Ok(to_dynamic(value))
+    }
+
+    fn set(&mut self, entity: Entity, path: &str, value: Dynamic) -> ScriptResult<()> {
+        let value = from_dynamic(value)?;
+        let (type_id, ptr, field_path) = self.component_path(entity, path)?;
+        unsafe { self.registry().set_path(&type_id, ptr, field_path, &value) }
+            .map_err(|err| err.to_string().into())
+    }
+
+    fn has(&self, entity: Entity, name: &str) -> ScriptResult<bool> {
+        let type_id = self.registration(name)?.type_id();
+        Ok(self.world().component_ptr(&type_id, entity).is_some())
+    }
+
+    fn spawn(&mut self) -> ScriptResult<Entity> {
+        let world = unsafe { &*self.world };
+        Ok(self.commands().spawn(world).entity())
+    }
+
+    fn despawn(&mut self, entity: Entity) -> ScriptResult<()> {
+        self.commands().despawn(entity);
+        Ok(())
+    }
+
+    fn insert(&mut self, entity: Entity, name: &str, value: Dynamic) -> ScriptResult<()> {
+        let value = from_dynamic(value)?;
+        let registration = self.registration(name)?.clone();
+        self.commands().push_writer(InsertCommand {
+            entity,
+            registration,
+            value,
+        });
+        Ok(())
+    }
+
+    fn remove(&mut self, entity: Entity, name: &str) -> ScriptResult<()> {
+        let type_id = self.registration(name)?.type_id();
+        self.commands()
+            .push_writer(RemoveCommand { entity, type_id });
+        Ok(())
+    }
+
+    fn send(&mut self, name: &str, event: Dynamic) -> ScriptResult<()> {
+        let event = from_dynamic(event)?;
+        self.events()
+            .send(name, event)
+            .map_err(|name| format!("event '{}' is not registered", name).into())
+    }
+
+    fn read(&mut self, name: &str) -> ScriptResult<Array> {
+        let events = self
+            .events()
+            .read(name)
+            .ok_or_else(|| format!("event '{}' is not registered", name))?;
+        Ok(events.iter().cloned().map(to_dynamic).collect())
+    }
+}
+
+struct InsertCommand {
+    entity: Entity,
+    registration: ComponentRegistration,
+    value: app::Value,
+}
+
+impl WorldWritable for InsertCommand {
+    fn write(self: Arc<Self>, world: &mut World, _cmd: &CommandBuffer) {
+        if let Err(err) = self.registration.insert(world, self.entity, &self.value) {
+            tracing::warn!(
+                component = self.registration.short_name(),
+                "script failed to insert component: {}",
+                err
+            );
+        }
+    }
+}
+
+struct RemoveCommand {
+    entity: Entity,
+    type_id: ComponentTypeId,
+}
+
+impl WorldWritable for RemoveCommand {
+    fn write(self: Arc<Self>, world: &mut World, _cmd: &CommandBuffer) {
+        world.remove_component_raw(self.entity, self.type_id);
+    }
+}
+
+pub(crate) fn register_api(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Entity>("Entity")
+        .register_get("id", |entity: &mut Entity| entity.id() as INT)
+        .register_fn("to_string", |entity: &mut Entity| format!("{:?}", entity))
+        .register_fn("to_debug", |entity: &mut Entity| format!("{:?}", entity))
+        .register_fn("==", |a: Entity, b: Entity| a == b)
+        .register_fn("!=", |a: Entity, b: Entity| a != b);
+
+    engine
+        .register_type_with_name::<ScriptContext>("Context")
+        .register_fn("query", |ctx: &mut ScriptContext, names: Array| {
+            ctx.with(|data| data.query(names))
+        })
+        .register_fn(
+            "get",
+            |ctx: &mut ScriptContext, entity: Entity, path: &str| {
+                ctx.with(|data| data.get(entity, path))
+            },
+        )
+        .register_fn(
+            "set",
+            |ctx: &mut ScriptContext, entity: Entity, path: &str, value: Dynamic| {
+                ctx.with(|data| data.set(entity, path, value))
+            },
+        )
+        .register_fn(
+            "has",
+            |ctx: &mut ScriptContext, entity: Entity, name: &str| {
+                ctx.with(|data| data.has(entity, name))
+            },
+        )
+        .register_fn("spawn_entity", |ctx: &mut ScriptContext| {
+            ctx.with(|data| data.spawn())
+        })
+        .register_fn(
+            "despawn_entity",
+            |ctx: &mut ScriptContext, entity: Entity| ctx.with(|data| data.despawn(entity)),
+        )
+        .register_fn(
+            "insert",
+            |ctx: &mut ScriptContext, entity: Entity, name: &str, value: Dynamic| {
+                ctx.with(|data| data.insert(entity, name, value))
+            },
+        )
+        .register_fn(
+            "remove",
+            |ctx: &mut ScriptContext, entity: Entity, name: &str| {
+                ctx.with(|data| data.remove(entity, name))
+            },
+        )
+        .register_fn(
+            "send",
+            |ctx: &mut ScriptContext, name: &str, event: Dynamic| {
+                ctx.with(|data| data.send(name, event))
+            },
+        )
+        .register_fn("read", |ctx: &mut ScriptContext, name: &str| {
+            ctx.with(|data| data.read(name))
+        });
+}
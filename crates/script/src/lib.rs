@@ -0,0 +1,268 @@
+mod context;
+mod scripts;
+mod value;
+
+use std::path::PathBuf;
+
+use app::{
+    App, AppStage, EventReader, Events, ParRunnable, Plugin, Reflect, StageLabel, SystemBuilder,
+    TypeRegistry,
+};
+
+pub use scripts::*;
+pub use value::*;
+
+type EventBridgeFn = fn(&mut App, &str, &dyn StageLabel);
+
+/// Run Rhai scripts as systems. A script registers its systems when loaded:
+///
+/// ```rhai
+/// register_system("update", "move");
+///
+/// fn move(ctx) {
+///     for entity in ctx.query(["Position", "Velocity"]) {
+///         ctx.set(entity, "Position.x", ctx.get(entity, "Position.x") + ctx.get(entity, "Velocity.x"));
+///     }
+/// }
+/// ```
+///
+/// Components are reached through the `TypeRegistry`, so they must be
+/// registered with `App::register_component`.
+pub struct ScriptPlugin {
+    scripts: Vec<PathBuf>,
+    stages: Vec<(String, Box<dyn StageLabel>)>,
+    events: Vec<(String, EventBridgeFn)>,
+}
+
+impl Default for ScriptPlugin {
+    fn default() -> Self {
+        ScriptPlugin {
+            scripts: Vec::new(),
+            stages: vec![
+                ("pre_update".to_string(), Box::new(AppStage::PreUpdate)),
+                ("update".to_string(), Box::new(AppStage::Update)),
+                ("post_update".to_string(), Box::new(AppStage::PostUpdate)),
+            ],
+            events: Vec::new(),
+        }
+    }
+}
+
+impl ScriptPlugin {
+    pub fn with_script(mut self, path: impl Into<PathBuf>) -> Self {
+        self.scripts.push(path.into());
+        self
+    }
+
+    /// Let scripts register systems on `label` under `name`.
+    pub fn with_stage(mut self, name: &str, label: impl StageLabel) -> Self {
+        self.stages.push((name.to_string(), Box::new(label)));
+        self
+    }
+
+    /// Let scripts send and read `Events<T>` under `name`.
+    pub fn with_event<T>(mut self, name: &str) -> Self
+    where
+        T: Reflect + Default,
+    {
+        self.events.push((name.to_string(), add_event_bridge::<T>));
+        self
+    }
+}
+
+impl Plugin for ScriptPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.resources.get_mut_or_default::<TypeRegistry>();
+
+        let mut scripts = Scripts::new(self.stages.iter().map(|(name, _)| name.clone()).collect());
+        for path in self.scripts.iter() {
+            scripts.add(path.clone());
+        }
+        let mut script_events = ScriptEvents::default();
+        for (name, _) in self.events.iter() {
+            script_events.register(name);
+        }
+        app.add_resource(scripts)
+            .add_resource(script_events)
+            .add_system(reload_sys());
+
+        for (stage, label) in self.stages.iter() {
+            for (name, add_bridge) in self.events.iter() {
+                add_bridge(app, name, label.as_ref());
+            }
+            app.add_system_to_stage(label.dyn_clone(), run_scripts_sys(stage.clone()));
+        }
+    }
+}
+
+fn reload_sys() -> impl ParRunnable {
+    SystemBuilder::new()
+        .on_stage(AppStage::Begin)
+        .write_resource::<Scripts>()
+        .build(|_, _, scripts, _| scripts.reload())
+}
+
+fn run_scripts_sys(stage: String) -> impl ParRunnable {
+    SystemBuilder::new()
+        .exclusive()
+        .read_resource::<TypeRegistry>()
+        .write_resource::<Scripts>()
+        .write_resource::<ScriptEvents>()
+        .build(move |world, commands, (registry, scripts, events), _| {
+            scripts.run_stage(&stage, world, commands, registry, events)
+        })
+}
+
+/// Move the events scripts sent into `Events<T>` and hand the new ones to
+/// scripts, before the script systems of a stage.
+fn add_event_bridge<T: Reflect + Default>(app: &mut App, name: &str, stage: &dyn StageLabel) {
+    if !app.resources.contains::<Events<T>>() {
+        app.add_event::<T>();
+    }
+
+    let name = name.to_string();
+    let mut reader = EventReader::<T>::default();
+    app.add_system_to_stage(
+        stage.dyn_clone(),
+        SystemBuilder::new()
+            .write_resource::<Events<T>>()
+            .write_resource::<ScriptEvents>()
+            .build(move |_, _, (events, script_events), _| {
+                for value in script_events.take_outgoing(&name) {
+                    let mut event = T::default();
+                    match event.apply(&value) {
+                        Ok(()) => events.send(event),
+                        Err(err) => {
+                            tracing::warn!(event = %name, "script sent an invalid event: {}", err)
+                        }
+                    }
+                }
+                let received = reader.iter(events).map(|event| event.to_value()).collect();
+                script_events.set_incoming(&name, received);
+            }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        time::{Duration, SystemTime},
+    };
+
+    use app::{Entities, Query, Reflect};
+
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Reflect)]
+    struct Position {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Debug, Default, PartialEq, Reflect)]
+    struct Velocity {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Reflect)]
+    struct Damage {
+        amount: i32,
+    }
+
+    const SCRIPT: &str = r#"
+        register_system("update", "movement");
+        register_system("post_update", "damage");
+
+        fn movement(ctx) {
+            for entity in ctx.query(["Position", "Velocity"]) {
+                ctx.set(entity, "Position.x", ctx.get(entity, "Position.x") + ctx.get(entity, "Velocity.x"));
+                if ctx.get(entity, "Position.x") > 2.0 {
+                    ctx.despawn_entity(entity);
+                    let spawned = ctx.spawn_entity();
+                    ctx.insert(spawned, "Position", #{ y: 5.0 });
+                    ctx.send("Damage", #{ amount: 1 });
+                }
+            }
+        }
+
+        fn damage(ctx) {
+            for event in ctx.read("Damage") {
+                ctx.send("Damage", #{ amount: event.amount * 10 });
+            }
+        }
+    "#;
+
+    #[test]
+    fn script_plugin() {
+        let path = std::env::temp_dir().join(format!("wigame_script_{}.rhai", std::process::id()));
+        fs::write(&path, SCRIPT).unwrap();
+
+        let mut app = App::new();
+        app.register_component::<Position>()
+            .register_component::<Velocity>()
+            .add_plugin(
+                ScriptPlugin::default()
+                    .with_script(&path)
+                    .with_event::<Damage>("Damage"),
+            );
+        let entity = app
+            .world
+            .spawn()
+            .add(Position::default())
+            .add(Velocity { x: 1.5, y: 0. })
+            .entity();
+
+        app.update();
+        assert_eq!(app.resources.get::<Scripts>().unwrap().errors().count(), 0);
+        assert_eq!(
+            Query::<&Position>::new().iter(&app.world),
+            vec![&Position { x: 1.5, y: 0. }]
+        );
+
+        app.update();
+        assert!(!app.world.is_live(entity));
+        assert_eq!(
+            Query::<(Entities, &Position)>::new()
+                .iter(&app.world)
+                .into_iter()
+                .map(|(_, position)| position)
+                .collect::<Vec<_>>(),
+            vec![&Position { x: 0., y: 5. }]
+        );
+        let mut reader = EventReader::<Damage>::default();
+        let events = app.resources.get::<Events<Damage>>().unwrap();
+        assert_eq!(
+            reader.iter(&events).cloned().collect::<Vec<_>>(),
+            vec![Damage { amount: 1 }]
+        );
+        drop(events);
+
+        app.update();
+        let events = app.resources.get::<Events<Damage>>().unwrap();
+        assert_eq!(
+            reader.iter(&events).cloned().collect::<Vec<_>>(),
+            vec![Damage { amount: 10 }]
+        );
+        drop(events);
+
+        fs::write(&path, "register_system(\"update\", \"missing\");").unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(1))
+            .unwrap();
+        app.update();
+        let scripts = app.resources.get::<Scripts>().unwrap();
+        assert_eq!(
+            scripts.systems().cloned().collect::<Vec<_>>(),
+            vec![ScriptSystem {
+                stage: "update".to_string(),
+                function: "missing".to_string()
+            }]
+        );
+        assert_eq!(scripts.errors().count(), 1);
+        drop(scripts);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
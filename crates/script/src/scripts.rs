@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use app::{CommandBuffer, TypeRegistry, Value, World};
+use rhai::{CallFnOptions, Dynamic, Engine, Scope, AST};
+use util::parking_lot::Mutex;
+
+use crate::context::{register_api, ScriptContext};
+
+/// Function of a script run every frame on a stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptSystem {
+    pub stage: String,
+    pub function: String,
+}
+
+struct Script {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    ast: Option<AST>,
+    systems: Vec<ScriptSystem>,
+    error: Option<String>,
+}
+
+/// Loaded scripts, reloaded when their file changes on disk.
+pub struct Scripts {
+    engine: Engine,
+    stages: Vec<String>,
+    registered: Arc<Mutex<Vec<ScriptSystem>>>,
+    scripts: Vec<Script>,
+}
+
+impl Scripts {
+    pub fn new(stages: Vec<String>) -> Self {
+        let registered = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = Engine::new();
+        register_api(&mut engine);
+        engine.register_fn("register_system", {
+            let registered = registered.clone();
+            move |stage: &str, function: &str| {
+                registered.lock().push(ScriptSystem {
+                    stage: stage.to_string(),
+                    function: function.to_string(),
+                })
+            }
+        });
+
+        Scripts {
+            engine,
+            stages,
+            registered,
+            scripts: Vec::new(),
+        }
+    }
+
+    /// Add a script file, loaded on the next `reload`.
+    pub fn add(&mut self, path: impl Into<PathBuf>) {
+        self.scripts.push(Script {
+            path: path.into(),
+            modified: None,
+            ast: None,
+            systems: Vec::new(),
+            error: None,
+        });
+    }
+
+    pub fn systems(&self) -> impl Iterator<Item = &ScriptSystem> {
+        self.scripts.iter().flat_map(|script| script.systems.iter())
+    }
+
+    /// Last load or run error of every failing script.
+    pub fn errors(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.scripts.iter().filter_map(|script| {
+            script
+                .error
+                .as_deref()
+                .map(|error| (script.path.as_path(), error))
+        })
+    }
+
+    /// Load the scripts whose file changed since they were last loaded.
+    pub fn reload(&mut self) {
+        for index in 0..self.scripts.len() {
+            let modified = fs::metadata(&self.scripts[index].path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            if modified.is_some() && modified != self.scripts[index].modified {
+                self.scripts[index].modified = modified;
+                self.load(index);
+            }
+        }
+    }
+
+    fn load(&mut self, index: usize) {
+        let engine = &self.engine;
+        let registered = &self.registered;
+        let stages = &self.stages;
+        let script = &mut self.scripts[index];
+        script.ast = None;
+        script.systems.clear();
+        script.error = None;
+
+        let result = fs::read_to_string(&script.path)
+            .map_err(|err| err.to_string())
+            .and_then(|source| engine.compile(&source).map_err(|err| err.to_string()))
+            .and_then(|ast| {
+                registered.lock().clear();
+                engine.run_ast(&ast).map_err(|err| err.to_string())?;
+                let systems = std::mem::take(&mut *registered.lock());
+                for system in systems.iter() {
+                    if !stages.contains(&system.stage) {
+                        return Err(format!("unknown stage '{}'", system.stage));
+                    }
+                }
+                Ok((ast, systems))
+            });
+
+        match result {
+            Ok((ast, systems)) => {
+                script.ast = Some(ast);
+                script.systems = systems;
+            }
+            Err(err) => {
+                tracing::warn!(path = ?script.path, "failed to load script: {}", err);
+                script.error = Some(err);
+            }
+        }
+    }
+
+    /// Run the systems of `stage`. A script stops running at its first error,
+    /// until its file changes.
+    pub(crate) fn run_stage(
+        &mut self,
+        stage: &str,
+        world: &World,
+        commands: &mut CommandBuffer,
+        registry: &TypeRegistry,
+        events: &mut ScriptEvents,
+    ) {
+        let context = ScriptContext::new(world, commands, registry, events);
+        let engine = &self.engine;
+        for script in self.scripts.iter_mut() {
+            let ast = match (&script.ast, &script.error) {
+                (Some(ast), None) => ast,
+                _ => continue,
+            };
+            for system in script.systems.iter().filter(|system| system.stage == stage) {
+                let result = engine.call_fn_with_options::<Dynamic>(
+                    CallFnOptions::new().eval_ast(false),
+                    &mut Scope::new(),
+                    ast,
+                    &system.function,
+                    (context.clone(),),
+                );
+                if let Err(err) = result {
+                    tracing::warn!(
+                        system = %system.function,
+                        path = ?script.path,
+                        "script system failed: {}",
+                        err
+                    );
+                    script.error = Some(err.to_string());
+                    break;
+                }
+            }
+        }
+        context.close();
+    }
+}
+
+/// Events exchanged between scripts and `Events<T>` of registered event types.
+#[derive(Default)]
+pub struct ScriptEvents {
+    outgoing: HashMap<String, Vec<Value>>,
+    incoming: HashMap<String, Vec<Value>>,
+}
+
+impl ScriptEvents {
+    pub fn register(&mut self, name: &str) {
+        self.incoming.entry(name.to_string()).or_default();
+        self.outgoing.entry(name.to_string()).or_default();
+    }
+
+    /// Queue an event, returning the name back if it is not registered.
+    pub fn send<'a>(&mut self, name: &'a str, event: Value) -> Result<(), &'a str> {
+        match self.outgoing.get_mut(name) {
+            Some(events) => {
+                events.push(event);
+                Ok(())
+            }
+            None => Err(name),
+        }
+    }
+
+    /// Events received since the previous script stage.
+    pub fn read(&self, name: &str) -> Option<&[Value]> {
+        self.incoming.get(name).map(|events| events.as_slice())
+    }
+
+    pub(crate) fn take_outgoing(&mut self, name: &str) -> Vec<Value> {
+        self.outgoing
+            .get_mut(name)
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set_incoming(&mut self, name: &str, events: Vec<Value>) {
+        self.incoming.insert(name.to_string(), events);
+    }
+}
@@ -0,0 +1,55 @@
+use app::{Entity, Value};
+use rhai::{Array, Dynamic, EvalAltResult, Map, INT};
+
+pub fn to_dynamic(value: Value) -> Dynamic {
+    match value {
+        Value::Unit => Dynamic::UNIT,
+        Value::Bool(value) => value.into(),
+        Value::Int(value) => value.into(),
+        Value::UInt(value) => (value as INT).into(),
+        Value::Float(value) => value.into(),
+        Value::String(value) => value.into(),
+        Value::Entity(entity) => Dynamic::from(entity),
+        Value::List(values) => {
+            Dynamic::from_array(values.into_iter().map(to_dynamic).collect::<Array>())
+        }
+        Value::Struct(fields) => Dynamic::from_map(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), to_dynamic(value)))
+                .collect::<Map>(),
+        ),
+    }
+}
+
+pub fn from_dynamic(value: Dynamic) -> Result<Value, Box<EvalAltResult>> {
+    if value.is::<()>() {
+        Ok(Value::Unit)
+    } else if let Ok(value) = value.as_bool() {
+        Ok(Value::Bool(value))
+    } else if let Ok(value) = value.as_int() {
+        Ok(Value::Int(value))
+    } else if let Ok(value) = value.as_float() {
+        Ok(Value::Float(value))
+    } else if value.is::<Entity>() {
+        Ok(Value::Entity(value.cast::<Entity>()))
+    } else if value.is_string() {
+        Ok(Value::String(value.into_string()?))
+    } else if value.is_array() {
+        let values = value
+            .into_array()?
+            .into_iter()
+            .map(from_dynamic)
+            .collect::<Result<_, _>>()?;
+        Ok(Value::List(values))
+    } else if value.is_map() {
+        let fields = value
+            .cast::<Map>()
+            .into_iter()
+            .map(|(name, value)| Ok((name.to_string(), from_dynamic(value)?)))
+            .collect::<Result<_, Box<EvalAltResult>>>()?;
+        Ok(Value::Struct(fields))
+    } else {
+        Err(format!("cannot convert {} to a component value", value.type_name()).into())
+    }
+}
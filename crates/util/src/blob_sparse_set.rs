@@ -37,6 +37,16 @@ impl<I: SparseIndex> BlobSparseSet<I> {
         self.sparse.bitset()
     }
 
+    #[inline]
+    pub fn layout(&self) -> Layout {
+        self.dense.layout()
+    }
+
+    #[inline]
+    pub fn drop_fn(&self) -> unsafe fn(*mut u8) {
+        self.dense.drop_fn()
+    }
+
     #[inline]
     pub fn capacity(&self) -> usize {
         self.dense.capacity()
@@ -50,6 +50,16 @@ impl BlobVec {
         self.get_ptr().as_ptr().add(index * self.layout.size())
     }
 
+    #[inline]
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    #[inline]
+    pub fn drop_fn(&self) -> unsafe fn(*mut u8) {
+        self.drop
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.len
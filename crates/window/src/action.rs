@@ -0,0 +1,458 @@
+use std::{
+    any::type_name,
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    hash::Hash,
+    io,
+    path::Path,
+};
+
+use app::{App, AppStage, EventReader, Events, ParRunnable, Plugin, SystemBuilder};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use winit::event::{ModifiersState, MouseButton, MouseScrollDelta, VirtualKeyCode};
+
+use crate::{Input, WindowMouseWheel, WindowPlugin};
+
+/// Wheel pixel deltas, from touchpads, are converted to lines with this ratio.
+const PIXELS_PER_LINE: f32 = 20.;
+
+pub trait Action: Copy + Eq + Hash + Ord + Send + Sync + 'static {}
+impl<T> Action for T where T: Copy + Eq + Hash + Ord + Send + Sync + 'static {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputButton {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+    /// Held for the frames the wheel scrolled up.
+    WheelUp,
+    WheelDown,
+    /// Either control key.
+    Ctrl,
+    Shift,
+    Alt,
+    Logo,
+}
+
+impl From<VirtualKeyCode> for InputButton {
+    fn from(key_code: VirtualKeyCode) -> Self {
+        InputButton::Key(key_code)
+    }
+}
+
+impl From<MouseButton> for InputButton {
+    fn from(button: MouseButton) -> Self {
+        InputButton::Mouse(button)
+    }
+}
+
+/// Buttons that must be held together. When the buttons of a binding are part
+/// of a longer active chord, only the chord triggers: `Ctrl+S` does not trigger
+/// `S`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Binding(Vec<InputButton>);
+
+impl Binding {
+    pub fn chord(buttons: impl IntoIterator<Item = InputButton>) -> Self {
+        Binding(buttons.into_iter().collect())
+    }
+
+    pub fn buttons(&self) -> &[InputButton] {
+        &self.0
+    }
+
+    fn contains(&self, other: &Binding) -> bool {
+        self.0.len() > other.0.len() && other.0.iter().all(|button| self.0.contains(button))
+    }
+}
+
+impl<T: Into<InputButton>> From<T> for Binding {
+    fn from(button: T) -> Self {
+        Binding(vec![button.into()])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AxisBinding {
+    /// -1 while `negative` is held, 1 while `positive` is.
+    Buttons {
+        negative: Binding,
+        positive: Binding,
+    },
+    /// Lines scrolled this frame.
+    WheelX,
+    WheelY,
+}
+
+#[derive(Debug)]
+pub enum ActionMapError {
+    Io(io::Error),
+    Ron(ron::Error),
+}
+
+impl fmt::Display for ActionMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionMapError::Io(err) => write!(f, "{}", err),
+            ActionMapError::Ron(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ActionMapError {}
+
+impl From<io::Error> for ActionMapError {
+    fn from(err: io::Error) -> Self {
+        ActionMapError::Io(err)
+    }
+}
+
+impl From<ron::Error> for ActionMapError {
+    fn from(err: ron::Error) -> Self {
+        ActionMapError::Ron(err)
+    }
+}
+
+/// Bindings of the actions and axes, the resource can be changed at runtime to
+/// rebind them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "A: Serialize", deserialize = "A: Ord + Deserialize<'de>"))]
+pub struct ActionMap<A> {
+    actions: BTreeMap<A, Vec<Binding>>,
+    axes: BTreeMap<A, Vec<AxisBinding>>,
+}
+
+impl<A> Default for ActionMap<A> {
+    fn default() -> Self {
+        ActionMap {
+            actions: BTreeMap::new(),
+            axes: BTreeMap::new(),
+        }
+    }
+}
+
+impl<A: Action> ActionMap<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind(&mut self, action: A, binding: impl Into<Binding>) -> &mut Self {
+        let binding = binding.into();
+        let bindings = self.actions.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    pub fn bind_axis(&mut self, axis: A, binding: AxisBinding) -> &mut Self {
+        let bindings = self.axes.entry(axis).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    /// Return whether the binding existed.
+    pub fn unbind(&mut self, action: A, binding: &Binding) -> bool {
+        match self.actions.get_mut(&action) {
+            Some(bindings) => {
+                let len = bindings.len();
+                bindings.retain(|other| other != binding);
+                bindings.len() != len
+            }
+            None => false,
+        }
+    }
+
+    /// Remove every binding of the action or axis.
+    pub fn clear(&mut self, action: A) {
+        self.actions.remove(&action);
+        self.axes.remove(&action);
+    }
+
+    pub fn bindings(&self, action: A) -> &[Binding] {
+        self.actions.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn axis_bindings(&self, axis: A) -> &[AxisBinding] {
+        self.axes.get(&axis).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<A> ActionMap<A>
+where
+    A: Action + Serialize + for<'de> Deserialize<'de>,
+{
+    pub fn to_ron(&self) -> Result<String, ActionMapError> {
+        Ok(ron::ser::to_string_pretty(self, PrettyConfig::default())?)
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self, ActionMapError> {
+        Ok(ron::de::from_str(ron)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ActionMapError> {
+        Self::from_ron(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ActionMapError> {
+        Ok(fs::write(path, self.to_ron()?)?)
+    }
+}
+
+/// State of the actions and axes of an `ActionMap`, updated on
+/// `AppStage::PreUpdate`.
+pub struct ActionState<A> {
+    actions: Input<A>,
+    axes: HashMap<A, f32>,
+}
+
+impl<A> Default for ActionState<A> {
+    fn default() -> Self {
+        ActionState {
+            actions: Input::default(),
+            axes: HashMap::new(),
+        }
+    }
+}
+
+impl<A: Action> ActionState<A> {
+    pub fn pressed(&self, action: A) -> bool {
+        self.actions.pressed(action)
+    }
+
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.actions.just_pressed(action)
+    }
+
+    pub fn just_released(&self, action: A) -> bool {
+        self.actions.just_released(action)
+    }
+
+    /// Sum of the axis bindings, the button bindings together stay within
+    /// -1 and 1.
+    pub fn axis(&self, axis: A) -> f32 {
+        self.axes.get(&axis).copied().unwrap_or_default()
+    }
+}
+
+struct ButtonState<'a> {
+    keys: &'a Input<VirtualKeyCode>,
+    mouse_buttons: &'a Input<MouseButton>,
+    modifiers: ModifiersState,
+    wheel: (f32, f32),
+}
+
+impl ButtonState<'_> {
+    fn pressed(&self, button: InputButton) -> bool {
+        match button {
+            InputButton::Key(key_code) => self.keys.pressed(key_code),
+            InputButton::Mouse(button) => self.mouse_buttons.pressed(button),
+            InputButton::WheelUp => self.wheel.1 > 0.,
+            InputButton::WheelDown => self.wheel.1 < 0.,
+            InputButton::Ctrl => self.modifiers.ctrl(),
+            InputButton::Shift => self.modifiers.shift(),
+            InputButton::Alt => self.modifiers.alt(),
+            InputButton::Logo => self.modifiers.logo(),
+        }
+    }
+}
+
+impl<A: Action> ActionState<A> {
+    fn update(&mut self, map: &ActionMap<A>, buttons: &ButtonState) {
+        let held = map
+            .actions
+            .values()
+            .flatten()
+            .chain(map.axes.values().flatten().flat_map(|axis| match axis {
+                AxisBinding::Buttons { negative, positive } => vec![negative, positive],
+                _ => Vec::new(),
+            }))
+            .filter(|binding| {
+                binding
+                    .buttons()
+                    .iter()
+                    .all(|button| buttons.pressed(*button))
+            })
+            .collect::<Vec<_>>();
+        let active = |binding: &Binding| {
+            held.contains(&binding) && !held.iter().any(|other| other.contains(binding))
+        };
+
+        self.actions.clear();
+        for (action, bindings) in map.actions.iter() {
+            if bindings.iter().any(active) {
+                self.actions.press(*action);
+            } else {
+                self.actions.release(*action);
+            }
+        }
+
+        self.axes.clear();
+        for (axis, bindings) in map.axes.iter() {
+            let mut buttons_value: f32 = 0.;
+            let mut wheel_value = 0.;
+            for binding in bindings {
+                match binding {
+                    AxisBinding::Buttons { negative, positive } => {
+                        if active(negative) {
+                            buttons_value -= 1.;
+                        }
+                        if active(positive) {
+                            buttons_value += 1.;
+                        }
+                    }
+                    AxisBinding::WheelX => wheel_value += buttons.wheel.0,
+                    AxisBinding::WheelY => wheel_value += buttons.wheel.1,
+                }
+            }
+            self.axes
+                .insert(*axis, buttons_value.clamp(-1., 1.) + wheel_value);
+        }
+    }
+}
+
+/// Add the `ActionMap<A>` and `ActionState<A>` resources.
+pub struct ActionPlugin<A> {
+    map: Option<ActionMap<A>>,
+}
+
+impl<A: Action> ActionPlugin<A> {
+    pub fn new(map: ActionMap<A>) -> Self {
+        ActionPlugin { map: Some(map) }
+    }
+}
+
+impl<A: Action> Plugin for ActionPlugin<A> {
+    fn build(&mut self, app: &mut App) {
+        app.add_resource(self.map.take().unwrap_or_default())
+            .add_resource(ActionState::<A>::default())
+            .add_system(action_sys::<A>());
+    }
+
+    fn dependencies(&self) -> Vec<&'static str> {
+        vec![type_name::<WindowPlugin>()]
+    }
+}
+
+fn action_sys<A: Action>() -> impl ParRunnable {
+    let mut wheel_reader = EventReader::<WindowMouseWheel>::default();
+
+    // Runs after the input system of `WindowPlugin`, added earlier to the same
+    // sequence stage.
+    SystemBuilder::new()
+        .on_stage(AppStage::PreUpdate)
+        .read_resource::<ActionMap<A>>()
+        .read_resource::<Input<VirtualKeyCode>>()
+        .read_resource::<Input<MouseButton>>()
+        .read_resource::<ModifiersState>()
+        .read_resource::<Events<WindowMouseWheel>>()
+        .write_resource::<ActionState<A>>()
+        .build(move |_, _, resources, _| {
+            let (map, keys, mouse_buttons, modifiers, wheel_events, state) = resources;
+            let wheel = wheel_reader
+                .iter(wheel_events)
+                .fold((0., 0.), |(x, y), event| match event.delta {
+                    MouseScrollDelta::LineDelta(dx, dy) => (x + dx, y + dy),
+                    MouseScrollDelta::PixelDelta(delta) => (
+                        x + delta.x as f32 / PIXELS_PER_LINE,
+                        y + delta.y as f32 / PIXELS_PER_LINE,
+                    ),
+                });
+            let buttons = ButtonState {
+                keys,
+                mouse_buttons,
+                modifiers: **modifiers,
+                wheel,
+            };
+            state.update(map, &buttons);
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::{event::ElementState, window::WindowId};
+
+    use crate::{input::send_key, WindowModifiersChanged};
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+    enum Control {
+        Jump,
+        Save,
+        Forward,
+        Zoom,
+    }
+
+    #[test]
+    fn action_map() {
+        let mut map = ActionMap::new();
+        map.bind(Control::Jump, VirtualKeyCode::Space)
+            .bind(
+                Control::Save,
+                Binding::chord([InputButton::Ctrl, VirtualKeyCode::S.into()]),
+            )
+            .bind_axis(
+                Control::Forward,
+                AxisBinding::Buttons {
+                    negative: VirtualKeyCode::S.into(),
+                    positive: VirtualKeyCode::W.into(),
+                },
+            )
+            .bind_axis(Control::Zoom, AxisBinding::WheelY);
+        assert_eq!(ActionMap::from_ron(&map.to_ron().unwrap()).unwrap(), map);
+
+        let mut app = App::new();
+        app.add_plugin(WindowPlugin::default())
+            .add_plugin(ActionPlugin::new(map));
+        let id = unsafe { WindowId::dummy() };
+
+        send_key(&mut app, VirtualKeyCode::Space, ElementState::Pressed);
+        send_key(&mut app, VirtualKeyCode::S, ElementState::Pressed);
+        app.update();
+        {
+            let state = app.resources.get::<ActionState<Control>>().unwrap();
+            assert!(state.just_pressed(Control::Jump));
+            assert!(!state.pressed(Control::Save));
+            assert_eq!(state.axis(Control::Forward), -1.);
+        }
+
+        // The chord takes over the S key.
+        app.resources
+            .get_mut::<Events<WindowModifiersChanged>>()
+            .unwrap()
+            .send(WindowModifiersChanged {
+                id,
+                modifiers: ModifiersState::CTRL,
+            });
+        app.resources
+            .get_mut::<Events<WindowMouseWheel>>()
+            .unwrap()
+            .send(WindowMouseWheel {
+                id,
+                delta: MouseScrollDelta::LineDelta(0., 2.),
+            });
+        app.update();
+        {
+            let state = app.resources.get::<ActionState<Control>>().unwrap();
+            assert!(state.pressed(Control::Jump));
+            assert!(!state.just_pressed(Control::Jump));
+            assert!(state.just_pressed(Control::Save));
+            assert_eq!(state.axis(Control::Forward), 0.);
+            assert_eq!(state.axis(Control::Zoom), 2.);
+        }
+
+        // Rebind at runtime.
+        {
+            let mut map = app.resources.get_mut::<ActionMap<Control>>().unwrap();
+            assert!(map.unbind(Control::Jump, &VirtualKeyCode::Space.into()));
+            map.bind(Control::Jump, MouseButton::Left);
+        }
+        app.update();
+        let state = app.resources.get::<ActionState<Control>>().unwrap();
+        assert!(state.just_released(Control::Jump));
+        assert_eq!(state.axis(Control::Zoom), 0.);
+    }
+}
@@ -1,10 +1,16 @@
+use std::path::PathBuf;
+
+use app::Entity;
 use winit::{
-    event::{ElementState, ScanCode, VirtualKeyCode},
-    window::WindowId,
+    event::{
+        ElementState, Ime, ModifiersState, MouseButton, MouseScrollDelta, ScanCode, VirtualKeyCode,
+    },
+    window::{CursorIcon, Theme, WindowId},
 };
 
-use crate::WindowDescriptor;
+use crate::{WindowDescriptor, WindowMode};
 
+/// Spawn a `Window` entity for the descriptor.
 #[derive(Debug)]
 pub struct WindowCreateRequest {
     pub descriptor: WindowDescriptor,
@@ -13,8 +19,11 @@ pub struct WindowCreateRequest {
 #[derive(Debug)]
 pub struct WindowCreated {
     pub id: WindowId,
+    pub entity: Entity,
 }
 
+/// Sent when the user asks to close a window, the entity of the window is then
+/// despawned.
 #[derive(Debug)]
 pub struct WindowCloseRequest {
     pub id: WindowId,
@@ -25,6 +34,62 @@ pub struct WindowClosed {
     pub id: WindowId,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowCommandKind {
+    SetTitle(String),
+    /// Inner size in physical pixels.
+    SetSize {
+        width: u32,
+        height: u32,
+    },
+    /// Outer position in physical pixels.
+    SetPosition {
+        x: i32,
+        y: i32,
+    },
+    SetMode(WindowMode),
+    /// Keep the cursor inside the window.
+    SetCursorGrab(bool),
+    SetCursorVisible(bool),
+    SetCursorIcon(CursorIcon),
+    SetMinimized(bool),
+    SetMaximized(bool),
+    /// See `WindowManager::set_ime_allowed`.
+    SetImeAllowed(bool),
+    /// Position of the IME candidate window in physical pixels.
+    SetImePosition {
+        x: i32,
+        y: i32,
+    },
+    RequestRedraw,
+}
+
+impl WindowCommandKind {
+    /// Update the descriptor fields the command changes, the others are left
+    /// as they are.
+    pub(crate) fn update_descriptor(&self, descriptor: &mut WindowDescriptor) {
+        match self {
+            WindowCommandKind::SetTitle(title) => descriptor.title = title.clone(),
+            &WindowCommandKind::SetSize { width, height } => {
+                descriptor.width = width;
+                descriptor.height = height;
+            }
+            &WindowCommandKind::SetPosition { x, y } => descriptor.position = Some((x, y)),
+            &WindowCommandKind::SetMode(mode) => descriptor.mode = mode,
+            &WindowCommandKind::SetCursorVisible(visible) => descriptor.cursor_visible = visible,
+            _ => {}
+        }
+    }
+}
+
+/// Change a window at runtime, applied by the runner on the main thread before
+/// the next update.
+#[derive(Debug)]
+pub struct WindowCommand {
+    pub id: WindowId,
+    pub command: WindowCommandKind,
+}
+
 #[derive(Debug)]
 pub struct WindowResized {
     pub id: WindowId,
@@ -32,8 +97,110 @@ pub struct WindowResized {
     pub height: u32,
 }
 
+/// Outer position in physical pixels.
+#[derive(Debug)]
+pub struct WindowMoved {
+    pub id: WindowId,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Sent along with `WindowResized` for the new physical size.
+#[derive(Debug)]
+pub struct WindowScaleFactorChanged {
+    pub id: WindowId,
+    pub scale_factor: f64,
+    pub width: u32,
+    pub height: u32,
+    pub logical_width: f64,
+    pub logical_height: f64,
+}
+
+#[derive(Debug)]
+pub struct WindowThemeChanged {
+    pub id: WindowId,
+    pub theme: Theme,
+}
+
+/// A file dragged over a window. Dropping several files sends one event per
+/// file.
+#[derive(Debug)]
+pub enum FileDragAndDrop {
+    HoveredFile { id: WindowId, path: PathBuf },
+    DroppedFile { id: WindowId, path: PathBuf },
+    HoveredFileCancelled { id: WindowId },
+}
+
 pub struct WindowKeyboardInput {
     pub key_code: Option<VirtualKeyCode>,
     pub scan_code: ScanCode,
     pub state: ElementState,
 }
+
+#[derive(Debug)]
+pub struct WindowModifiersChanged {
+    pub id: WindowId,
+    pub modifiers: ModifiersState,
+}
+
+#[derive(Debug)]
+pub struct WindowFocused {
+    pub id: WindowId,
+    pub focused: bool,
+}
+
+/// Cursor position in physical pixels, relative to the top-left corner of the
+/// window.
+#[derive(Debug)]
+pub struct WindowCursorMoved {
+    pub id: WindowId,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug)]
+pub struct WindowCursorEntered {
+    pub id: WindowId,
+}
+
+#[derive(Debug)]
+pub struct WindowCursorLeft {
+    pub id: WindowId,
+}
+
+#[derive(Debug)]
+pub struct WindowMouseButtonInput {
+    pub id: WindowId,
+    pub button: MouseButton,
+    pub state: ElementState,
+}
+
+#[derive(Debug)]
+pub struct WindowMouseWheel {
+    pub id: WindowId,
+    pub delta: MouseScrollDelta,
+}
+
+/// Raw mouse movement, not bound to a window nor clamped to the screen, for
+/// camera controls.
+#[derive(Debug)]
+pub struct MouseMotion {
+    pub dx: f64,
+    pub dy: f64,
+}
+
+/// Character typed in a window. While IME is allowed, text composed with the
+/// input method arrives as `WindowIme` instead.
+#[derive(Debug)]
+pub struct WindowCharacterInput {
+    pub id: WindowId,
+    pub character: char,
+}
+
+/// Input method composition of a window, only sent while IME is allowed on it,
+/// see `WindowManager::set_ime_allowed`.
+#[derive(Debug)]
+pub struct WindowIme {
+    pub id: WindowId,
+    pub ime: Ime,
+}
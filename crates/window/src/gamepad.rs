@@ -0,0 +1,565 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use app::{App, AppStage, Events, ParRunnable, Plugin, SystemBuilder};
+use serde::{Deserialize, Serialize};
+use winit::event::ElementState;
+
+use crate::Input;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GamepadId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    LeftZ,
+    RightStickX,
+    RightStickY,
+    RightZ,
+}
+
+/// Raw event of a gamepad backend, values are not filtered yet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GamepadEvent {
+    Connected {
+        id: GamepadId,
+        name: String,
+    },
+    Disconnected {
+        id: GamepadId,
+    },
+    /// Value between 0 and 1.
+    Button {
+        id: GamepadId,
+        button: GamepadButton,
+        value: f32,
+    },
+    /// Value between -1 and 1.
+    Axis {
+        id: GamepadId,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+/// Source of gamepad events, polled on `AppStage::PreUpdate` on the main
+/// thread.
+pub trait GamepadBackend: 'static {
+    fn next_event(&mut self) -> Option<GamepadEvent>;
+}
+
+/// In-memory backend, for tests and input replay. Clones share the same event
+/// queue, so a handle can be kept after giving one to `GamepadPlugin`.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualGamepads {
+    events: Arc<Mutex<VecDeque<GamepadEvent>>>,
+    next_id: Arc<Mutex<usize>>,
+}
+
+impl VirtualGamepads {
+    pub fn send(&self, event: GamepadEvent) {
+        self.events.lock().unwrap().push_back(event);
+    }
+
+    pub fn connect(&self, name: &str) -> GamepadId {
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            GamepadId(*next_id - 1)
+        };
+        self.send(GamepadEvent::Connected {
+            id,
+            name: name.to_string(),
+        });
+        id
+    }
+
+    pub fn disconnect(&self, id: GamepadId) {
+        self.send(GamepadEvent::Disconnected { id });
+    }
+
+    pub fn set_button(&self, id: GamepadId, button: GamepadButton, value: f32) {
+        self.send(GamepadEvent::Button { id, button, value });
+    }
+
+    pub fn press(&self, id: GamepadId, button: GamepadButton) {
+        self.set_button(id, button, 1.);
+    }
+
+    pub fn release(&self, id: GamepadId, button: GamepadButton) {
+        self.set_button(id, button, 0.);
+    }
+
+    pub fn set_axis(&self, id: GamepadId, axis: GamepadAxis, value: f32) {
+        self.send(GamepadEvent::Axis { id, axis, value });
+    }
+}
+
+impl GamepadBackend for VirtualGamepads {
+    fn next_event(&mut self) -> Option<GamepadEvent> {
+        self.events.lock().unwrap().pop_front()
+    }
+}
+
+#[cfg(feature = "gilrs")]
+pub use self::gilrs_backend::GilrsBackend;
+
+#[cfg(feature = "gilrs")]
+mod gilrs_backend {
+    use gilrs::{Axis, Button, EventType, Gilrs};
+
+    use super::{GamepadAxis, GamepadBackend, GamepadButton, GamepadEvent, GamepadId};
+
+    pub struct GilrsBackend {
+        gilrs: Gilrs,
+    }
+
+    impl GilrsBackend {
+        pub fn new() -> Result<Self, gilrs::Error> {
+            Ok(GilrsBackend {
+                gilrs: Gilrs::new()?,
+            })
+        }
+    }
+
+    impl GamepadBackend for GilrsBackend {
+        fn next_event(&mut self) -> Option<GamepadEvent> {
+            while let Some(event) = self.gilrs.next_event() {
+                let id = GamepadId(event.id.into());
+                let event = match event.event {
+                    EventType::Connected => GamepadEvent::Connected {
+                        id,
+                        name: self.gilrs.gamepad(event.id).name().to_string(),
+                    },
+                    EventType::Disconnected => GamepadEvent::Disconnected { id },
+                    EventType::ButtonChanged(button, value, _) => match convert_button(button) {
+                        Some(button) => GamepadEvent::Button { id, button, value },
+                        None => continue,
+                    },
+                    EventType::AxisChanged(axis, value, _) => match convert_axis(axis) {
+                        Some(axis) => GamepadEvent::Axis { id, axis, value },
+                        None => continue,
+                    },
+                    // Presses are derived from the button values.
+                    _ => continue,
+                };
+                return Some(event);
+            }
+            None
+        }
+    }
+
+    fn convert_button(button: Button) -> Option<GamepadButton> {
+        Some(match button {
+            Button::South => GamepadButton::South,
+            Button::East => GamepadButton::East,
+            Button::North => GamepadButton::North,
+            Button::West => GamepadButton::West,
+            Button::LeftTrigger => GamepadButton::LeftTrigger,
+            Button::LeftTrigger2 => GamepadButton::LeftTrigger2,
+            Button::RightTrigger => GamepadButton::RightTrigger,
+            Button::RightTrigger2 => GamepadButton::RightTrigger2,
+            Button::Select => GamepadButton::Select,
+            Button::Start => GamepadButton::Start,
+            Button::Mode => GamepadButton::Mode,
+            Button::LeftThumb => GamepadButton::LeftThumb,
+            Button::RightThumb => GamepadButton::RightThumb,
+            Button::DPadUp => GamepadButton::DPadUp,
+            Button::DPadDown => GamepadButton::DPadDown,
+            Button::DPadLeft => GamepadButton::DPadLeft,
+            Button::DPadRight => GamepadButton::DPadRight,
+            _ => return None,
+        })
+    }
+
+    fn convert_axis(axis: Axis) -> Option<GamepadAxis> {
+        Some(match axis {
+            Axis::LeftStickX => GamepadAxis::LeftStickX,
+            Axis::LeftStickY => GamepadAxis::LeftStickY,
+            Axis::LeftZ => GamepadAxis::LeftZ,
+            Axis::RightStickX => GamepadAxis::RightStickX,
+            Axis::RightStickY => GamepadAxis::RightStickY,
+            Axis::RightZ => GamepadAxis::RightZ,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisCurve {
+    Linear,
+    /// `value.abs().powf(exponent)`, keeping the sign. Exponents above 1 give
+    /// finer control around the center.
+    Power(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisSettings {
+    /// Values below the dead zone are 0, the rest is rescaled to start at 0.
+    pub dead_zone: f32,
+    pub curve: AxisCurve,
+}
+
+impl Default for AxisSettings {
+    fn default() -> Self {
+        AxisSettings {
+            dead_zone: 0.1,
+            curve: AxisCurve::Linear,
+        }
+    }
+}
+
+impl AxisSettings {
+    pub fn apply(&self, value: f32) -> f32 {
+        let magnitude = value.abs();
+        if magnitude <= self.dead_zone {
+            return 0.;
+        }
+        let magnitude = ((magnitude - self.dead_zone) / (1. - self.dead_zone)).min(1.);
+        let magnitude = match self.curve {
+            AxisCurve::Linear => magnitude,
+            AxisCurve::Power(exponent) => magnitude.powf(exponent),
+        };
+        magnitude.copysign(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GamepadSettings {
+    pub axis: AxisSettings,
+    /// Settings of specific axes, instead of `axis`.
+    pub axis_overrides: HashMap<GamepadAxis, AxisSettings>,
+    /// Button value at which the button is pressed.
+    pub press_threshold: f32,
+    /// Button value below which a pressed button is released.
+    pub release_threshold: f32,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        GamepadSettings {
+            axis: AxisSettings::default(),
+            axis_overrides: HashMap::new(),
+            press_threshold: 0.75,
+            release_threshold: 0.65,
+        }
+    }
+}
+
+impl GamepadSettings {
+    pub fn axis_settings(&self, axis: GamepadAxis) -> &AxisSettings {
+        self.axis_overrides.get(&axis).unwrap_or(&self.axis)
+    }
+}
+
+#[derive(Debug)]
+pub struct GamepadConnected {
+    pub id: GamepadId,
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub struct GamepadDisconnected {
+    pub id: GamepadId,
+}
+
+#[derive(Debug)]
+pub struct GamepadButtonInput {
+    pub id: GamepadId,
+    pub button: GamepadButton,
+    pub state: ElementState,
+}
+
+/// Sent when the axis value changed once filtered by `GamepadSettings`.
+#[derive(Debug)]
+pub struct GamepadAxisChanged {
+    pub id: GamepadId,
+    pub axis: GamepadAxis,
+    pub value: f32,
+}
+
+#[derive(Default)]
+struct GamepadState {
+    name: String,
+    buttons: Input<GamepadButton>,
+    button_values: HashMap<GamepadButton, f32>,
+    axes: HashMap<GamepadAxis, f32>,
+}
+
+/// Connected gamepads and their state, updated on `AppStage::PreUpdate`.
+#[derive(Default)]
+pub struct Gamepads {
+    gamepads: BTreeMap<GamepadId, GamepadState>,
+}
+
+impl Gamepads {
+    pub fn iter(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.gamepads.keys().copied()
+    }
+
+    pub fn contains(&self, id: GamepadId) -> bool {
+        self.gamepads.contains_key(&id)
+    }
+
+    pub fn name(&self, id: GamepadId) -> Option<&str> {
+        self.gamepads.get(&id).map(|gamepad| gamepad.name.as_str())
+    }
+
+    pub fn pressed(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads
+            .get(&id)
+            .is_some_and(|gamepad| gamepad.buttons.pressed(button))
+    }
+
+    pub fn just_pressed(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads
+            .get(&id)
+            .is_some_and(|gamepad| gamepad.buttons.just_pressed(button))
+    }
+
+    pub fn just_released(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads
+            .get(&id)
+            .is_some_and(|gamepad| gamepad.buttons.just_released(button))
+    }
+
+    /// Analog value of the button, between 0 and 1.
+    pub fn button_value(&self, id: GamepadId, button: GamepadButton) -> f32 {
+        self.gamepads
+            .get(&id)
+            .and_then(|gamepad| gamepad.button_values.get(&button))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Axis value filtered by `GamepadSettings`.
+    pub fn axis(&self, id: GamepadId, axis: GamepadAxis) -> f32 {
+        self.gamepads
+            .get(&id)
+            .and_then(|gamepad| gamepad.axes.get(&axis))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Poll gamepads from a backend, gilrs with the `gilrs` feature by default.
+pub struct GamepadPlugin {
+    backend: Option<Box<dyn GamepadBackend>>,
+    pub settings: GamepadSettings,
+}
+
+impl GamepadPlugin {
+    pub fn with_backend(backend: impl GamepadBackend) -> Self {
+        GamepadPlugin {
+            backend: Some(Box::new(backend)),
+            settings: GamepadSettings::default(),
+        }
+    }
+}
+
+impl Default for GamepadPlugin {
+    #[cfg(feature = "gilrs")]
+    fn default() -> Self {
+        match GilrsBackend::new() {
+            Ok(backend) => GamepadPlugin::with_backend(backend),
+            Err(err) => {
+                tracing::warn!("gamepads are not available: {}", err);
+                GamepadPlugin::with_backend(VirtualGamepads::default())
+            }
+        }
+    }
+
+    #[cfg(not(feature = "gilrs"))]
+    fn default() -> Self {
+        GamepadPlugin::with_backend(VirtualGamepads::default())
+    }
+}
+
+impl Plugin for GamepadPlugin {
+    fn build(&mut self, app: &mut App) {
+        let backend = self
+            .backend
+            .take()
+            .unwrap_or_else(|| Box::new(VirtualGamepads::default()));
+        app.add_resource(backend)
+            .add_resource(self.settings.clone())
+            .add_resource(Gamepads::default())
+            .add_event::<GamepadConnected>()
+            .add_event::<GamepadDisconnected>()
+            .add_event::<GamepadButtonInput>()
+            .add_event::<GamepadAxisChanged>()
+            .add_system(gamepad_sys());
+    }
+}
+
+fn gamepad_sys() -> impl ParRunnable {
+    SystemBuilder::new()
+        .on_stage(AppStage::PreUpdate)
+        .write_non_send_resource::<Box<dyn GamepadBackend>>()
+        .read_resource::<GamepadSettings>()
+        .write_resource::<Gamepads>()
+        .write_resource::<Events<GamepadConnected>>()
+        .write_resource::<Events<GamepadDisconnected>>()
+        .write_resource::<Events<GamepadButtonInput>>()
+        .write_resource::<Events<GamepadAxisChanged>>()
+        .build(|_, _, resources, _| {
+            let (
+                backend,
+                settings,
+                gamepads,
+                connected_events,
+                disconnected_events,
+                button_events,
+                axis_events,
+            ) = resources;
+            for gamepad in gamepads.gamepads.values_mut() {
+                gamepad.buttons.clear();
+            }
+
+            while let Some(event) = backend.next_event() {
+                match event {
+                    GamepadEvent::Connected { id, name } => {
+                        gamepads.gamepads.insert(
+                            id,
+                            GamepadState {
+                                name: name.clone(),
+                                ..Default::default()
+                            },
+                        );
+                        connected_events.send(GamepadConnected { id, name });
+                    }
+                    GamepadEvent::Disconnected { id } => {
+                        if gamepads.gamepads.remove(&id).is_some() {
+                            disconnected_events.send(GamepadDisconnected { id });
+                        }
+                    }
+                    GamepadEvent::Button { id, button, value } => {
+                        let gamepad = match gamepads.gamepads.get_mut(&id) {
+                            Some(gamepad) => gamepad,
+                            None => continue,
+                        };
+                        gamepad.button_values.insert(button, value);
+                        let pressed = gamepad.buttons.pressed(button);
+                        if !pressed && value >= settings.press_threshold {
+                            gamepad.buttons.press(button);
+                            button_events.send(GamepadButtonInput {
+                                id,
+                                button,
+                                state: ElementState::Pressed,
+                            });
+                        } else if pressed && value <= settings.release_threshold {
+                            gamepad.buttons.release(button);
+                            button_events.send(GamepadButtonInput {
+                                id,
+                                button,
+                                state: ElementState::Released,
+                            });
+                        }
+                    }
+                    GamepadEvent::Axis { id, axis, value } => {
+                        let gamepad = match gamepads.gamepads.get_mut(&id) {
+                            Some(gamepad) => gamepad,
+                            None => continue,
+                        };
+                        let value = settings.axis_settings(axis).apply(value);
+                        let previous = gamepad.axes.insert(axis, value).unwrap_or_default();
+                        if previous != value {
+                            axis_events.send(GamepadAxisChanged { id, axis, value });
+                        }
+                    }
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axis_settings() {
+        let linear = AxisSettings {
+            dead_zone: 0.2,
+            curve: AxisCurve::Linear,
+        };
+        assert_eq!(linear.apply(0.1), 0.);
+        assert_eq!(linear.apply(-0.2), 0.);
+        assert!((linear.apply(0.6) - 0.5).abs() < 1e-6);
+        assert_eq!(linear.apply(-1.), -1.);
+
+        let squared = AxisSettings {
+            dead_zone: 0.,
+            curve: AxisCurve::Power(2.),
+        };
+        assert!((squared.apply(-0.5) + 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn virtual_gamepads() {
+        let gamepads = VirtualGamepads::default();
+        let mut app = App::new();
+        app.add_plugin(GamepadPlugin::with_backend(gamepads.clone()));
+
+        let id = gamepads.connect("Virtual pad");
+        gamepads.press(id, GamepadButton::South);
+        gamepads.set_axis(id, GamepadAxis::LeftStickX, 0.05);
+        app.update();
+        {
+            let state = app.resources.get::<Gamepads>().unwrap();
+            assert_eq!(state.iter().collect::<Vec<_>>(), vec![id]);
+            assert_eq!(state.name(id), Some("Virtual pad"));
+            assert!(state.just_pressed(id, GamepadButton::South));
+            // Within the dead zone.
+            assert_eq!(state.axis(id, GamepadAxis::LeftStickX), 0.);
+            assert!(app
+                .resources
+                .get::<Events<GamepadAxisChanged>>()
+                .unwrap()
+                .is_empty());
+        }
+
+        // Between the release and press thresholds the button stays pressed.
+        gamepads.set_button(id, GamepadButton::South, 0.7);
+        gamepads.set_axis(id, GamepadAxis::LeftStickX, 1.);
+        app.update();
+        {
+            let state = app.resources.get::<Gamepads>().unwrap();
+            assert!(state.pressed(id, GamepadButton::South));
+            assert!(!state.just_pressed(id, GamepadButton::South));
+            assert_eq!(state.button_value(id, GamepadButton::South), 0.7);
+            assert_eq!(state.axis(id, GamepadAxis::LeftStickX), 1.);
+        }
+
+        gamepads.release(id, GamepadButton::South);
+        gamepads.disconnect(id);
+        app.update();
+        let state = app.resources.get::<Gamepads>().unwrap();
+        assert!(!state.contains(id));
+        assert!(!state.pressed(id, GamepadButton::South));
+    }
+}
@@ -0,0 +1,226 @@
+use std::{collections::HashSet, hash::Hash};
+
+use app::{AppStage, EventReader, Events, ParRunnable, SystemBuilder};
+use winit::event::{ElementState, ModifiersState, MouseButton, ScanCode, VirtualKeyCode};
+
+use crate::{WindowFocused, WindowKeyboardInput, WindowModifiersChanged, WindowMouseButtonInput};
+
+/// State of buttons or keys, updated on `AppStage::PreUpdate` from the window
+/// events. `just_pressed` and `just_released` only hold for the frame the
+/// change happened in.
+pub struct Input<T> {
+    pressed: HashSet<T>,
+    just_pressed: HashSet<T>,
+    just_released: HashSet<T>,
+}
+
+impl<T> Default for Input<T> {
+    fn default() -> Self {
+        Input {
+            pressed: HashSet::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+        }
+    }
+}
+
+impl<T> Input<T>
+where
+    T: Copy + Eq + Hash,
+{
+    pub fn press(&mut self, input: T) {
+        if self.pressed.insert(input) {
+            self.just_pressed.insert(input);
+        }
+    }
+
+    pub fn release(&mut self, input: T) {
+        if self.pressed.remove(&input) {
+            self.just_released.insert(input);
+        }
+    }
+
+    /// Release everything, when the window loses focus.
+    pub fn release_all(&mut self) {
+        self.just_released.extend(self.pressed.drain());
+    }
+
+    pub fn pressed(&self, input: T) -> bool {
+        self.pressed.contains(&input)
+    }
+
+    pub fn any_pressed(&self, inputs: impl IntoIterator<Item = T>) -> bool {
+        inputs.into_iter().any(|input| self.pressed(input))
+    }
+
+    pub fn just_pressed(&self, input: T) -> bool {
+        self.just_pressed.contains(&input)
+    }
+
+    pub fn just_released(&self, input: T) -> bool {
+        self.just_released.contains(&input)
+    }
+
+    pub fn get_pressed(&self) -> impl Iterator<Item = &T> {
+        self.pressed.iter()
+    }
+
+    pub fn get_just_pressed(&self) -> impl Iterator<Item = &T> {
+        self.just_pressed.iter()
+    }
+
+    pub fn get_just_released(&self) -> impl Iterator<Item = &T> {
+        self.just_released.iter()
+    }
+
+    /// Forget the changes of the last frame.
+    pub fn clear(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+}
+
+fn apply<T: Copy + Eq + Hash>(input: &mut Input<T>, value: T, state: ElementState) {
+    match state {
+        ElementState::Pressed => input.press(value),
+        ElementState::Released => input.release(value),
+    }
+}
+
+pub(crate) fn input_sys() -> impl ParRunnable {
+    let mut keyboard_reader = EventReader::<WindowKeyboardInput>::default();
+    let mut mouse_button_reader = EventReader::<WindowMouseButtonInput>::default();
+    let mut modifiers_reader = EventReader::<WindowModifiersChanged>::default();
+    let mut focused_reader = EventReader::<WindowFocused>::default();
+
+    SystemBuilder::new()
+        .on_stage(AppStage::PreUpdate)
+        .read_resource::<Events<WindowKeyboardInput>>()
+        .read_resource::<Events<WindowMouseButtonInput>>()
+        .read_resource::<Events<WindowModifiersChanged>>()
+        .read_resource::<Events<WindowFocused>>()
+        .write_resource::<Input<VirtualKeyCode>>()
+        .write_resource::<Input<ScanCode>>()
+        .write_resource::<Input<MouseButton>>()
+        .write_resource::<ModifiersState>()
+        .build(move |_, _, resources, _| {
+            let (
+                keyboard_events,
+                mouse_button_events,
+                modifiers_events,
+                focused_events,
+                key_codes,
+                scan_codes,
+                mouse_buttons,
+                modifiers,
+            ) = resources;
+            key_codes.clear();
+            scan_codes.clear();
+            mouse_buttons.clear();
+
+            for event in keyboard_reader.iter(keyboard_events) {
+                if let Some(key_code) = event.key_code {
+                    apply(&mut **key_codes, key_code, event.state);
+                }
+                apply(&mut **scan_codes, event.scan_code, event.state);
+            }
+            for event in mouse_button_reader.iter(mouse_button_events) {
+                apply(&mut **mouse_buttons, event.button, event.state);
+            }
+            if let Some(event) = modifiers_reader.iter(modifiers_events).next_back() {
+                **modifiers = event.modifiers;
+            }
+
+            // Releases are not reported once the window lost the focus. The
+            // events of the frame are applied first, a key pressed just before
+            // the focus left would stay pressed otherwise.
+            if focused_reader
+                .iter(focused_events)
+                .any(|event| !event.focused)
+            {
+                key_codes.release_all();
+                scan_codes.release_all();
+                mouse_buttons.release_all();
+                **modifiers = ModifiersState::empty();
+            }
+        })
+}
+
+/// Send a key event with scan code 17, for tests.
+#[cfg(test)]
+pub(crate) fn send_key(app: &mut app::App, key_code: VirtualKeyCode, state: ElementState) {
+    app.resources
+        .get_mut::<Events<WindowKeyboardInput>>()
+        .unwrap()
+        .send(WindowKeyboardInput {
+            key_code: Some(key_code),
+            scan_code: 17,
+            state,
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use app::App;
+
+    use crate::WindowPlugin;
+
+    use super::*;
+
+    #[test]
+    fn keyboard_input() {
+        let mut app = App::new();
+        app.add_plugin(WindowPlugin::default());
+
+        send_key(&mut app, VirtualKeyCode::W, ElementState::Pressed);
+        app.update();
+        {
+            let keys = app.resources.get::<Input<VirtualKeyCode>>().unwrap();
+            assert!(keys.pressed(VirtualKeyCode::W));
+            assert!(keys.just_pressed(VirtualKeyCode::W));
+            assert!(app.resources.get::<Input<ScanCode>>().unwrap().pressed(17));
+        }
+
+        // Key repeats do not press the key again.
+        send_key(&mut app, VirtualKeyCode::W, ElementState::Pressed);
+        app.update();
+        {
+            let keys = app.resources.get::<Input<VirtualKeyCode>>().unwrap();
+            assert!(keys.pressed(VirtualKeyCode::W));
+            assert!(!keys.just_pressed(VirtualKeyCode::W));
+        }
+
+        send_key(&mut app, VirtualKeyCode::W, ElementState::Released);
+        app.update();
+        {
+            let keys = app.resources.get::<Input<VirtualKeyCode>>().unwrap();
+            assert!(!keys.pressed(VirtualKeyCode::W));
+            assert!(keys.just_released(VirtualKeyCode::W));
+        }
+
+        app.update();
+        let keys = app.resources.get::<Input<VirtualKeyCode>>().unwrap();
+        assert!(!keys.just_released(VirtualKeyCode::W));
+    }
+
+    #[test]
+    fn focus_lost() {
+        let mut app = App::new();
+        app.add_plugin(WindowPlugin::default());
+
+        send_key(&mut app, VirtualKeyCode::W, ElementState::Pressed);
+        app.resources
+            .get_mut::<Events<WindowFocused>>()
+            .unwrap()
+            .send(WindowFocused {
+                id: unsafe { winit::window::WindowId::dummy() },
+                focused: false,
+            });
+        app.update();
+
+        let keys = app.resources.get::<Input<VirtualKeyCode>>().unwrap();
+        assert!(!keys.pressed(VirtualKeyCode::W));
+        assert!(keys.just_released(VirtualKeyCode::W));
+        assert!(!app.resources.get::<Input<ScanCode>>().unwrap().pressed(17));
+    }
+}
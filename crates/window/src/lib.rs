@@ -1,51 +1,179 @@
+pub mod action;
 pub mod events;
+pub mod gamepad;
+pub mod input;
 pub mod manager;
 mod runner;
+pub mod window;
 
-use app::{Events, Plugin};
+use std::path::PathBuf;
+
+use app::Plugin;
+use input::input_sys;
 use runner::{handle_window_event_sys, window_runner};
+use window::window_state_sys;
+use winit::event::{ModifiersState, MouseButton, ScanCode, VirtualKeyCode};
 
+pub use action::*;
 pub use events::*;
+pub use gamepad::*;
+pub use input::*;
 pub use manager::*;
+pub use window::*;
 pub use winit;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    /// Fullscreen window on the current monitor, without changing its video mode.
+    BorderlessFullscreen,
+    /// Fullscreen with the video mode of the primary monitor closest to the
+    /// window size.
+    Fullscreen,
+}
+
+/// Presentation preference of the window surface. Modes the device does not
+/// support fall back to `Fifo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresentMode {
+    /// No vsync, may tear.
+    Immediate,
+    /// Vsync without blocking, the latest frame is presented.
+    Mailbox,
+    /// Vsync, always supported.
+    Fifo,
+}
+
 #[derive(Debug, Clone)]
 pub struct WindowDescriptor {
     pub width: u32,
     pub height: u32,
     pub title: String,
+    pub resizable: bool,
+    pub min_size: Option<(u32, u32)>,
+    pub max_size: Option<(u32, u32)>,
+    pub mode: WindowMode,
+    /// Position of the top-left corner in physical pixels, chosen by the
+    /// platform when `None`.
+    pub position: Option<(i32, i32)>,
+    pub decorations: bool,
+    pub transparent: bool,
+    pub always_on_top: bool,
+    pub cursor_visible: bool,
+    /// Image file used as window icon.
+    pub icon: Option<PathBuf>,
+    pub present_mode: PresentMode,
+}
+
+impl Default for WindowDescriptor {
+    fn default() -> Self {
+        WindowDescriptor {
+            width: 800,
+            height: 600,
+            title: "WiGame".to_string(),
+            resizable: true,
+            min_size: None,
+            max_size: None,
+            mode: WindowMode::Windowed,
+            position: None,
+            decorations: true,
+            transparent: false,
+            always_on_top: false,
+            cursor_visible: true,
+            icon: None,
+            present_mode: PresentMode::Fifo,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCondition {
+    /// Exit once the window spawned with `PrimaryWindow` is closed.
+    OnPrimaryClosed,
+    OnAllClosed,
+    /// Keep running without windows, such as in the system tray.
+    DontExit,
+}
+
+/// Resource deciding what happens when windows are closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowClosePolicy {
+    /// Despawn the window entity on `WindowCloseRequest`. Otherwise only the
+    /// event is sent and the app despawns the entity itself, such as after a
+    /// confirmation dialog.
+    pub close_on_request: bool,
+    pub exit: ExitCondition,
+}
+
+impl Default for WindowClosePolicy {
+    fn default() -> Self {
+        WindowClosePolicy {
+            close_on_request: true,
+            exit: ExitCondition::OnAllClosed,
+        }
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct WindowPlugin {
     initial_window: Option<WindowDescriptor>,
+    close_policy: WindowClosePolicy,
 }
 
 impl WindowPlugin {
+    /// The initial window is spawned with `PrimaryWindow`.
     pub fn with_initial(descriptor: WindowDescriptor) -> Self {
         WindowPlugin {
             initial_window: Some(descriptor),
+            ..Default::default()
         }
     }
+
+    pub fn with_close_policy(mut self, close_policy: WindowClosePolicy) -> Self {
+        self.close_policy = close_policy;
+        self
+    }
 }
 
 impl Plugin for WindowPlugin {
     fn build(&mut self, app: &mut app::App) {
         app.add_resource(WindowManager::default())
+            .add_resource(self.close_policy)
             .add_event::<WindowCreateRequest>()
             .add_event::<WindowCreated>()
             .add_event::<WindowCloseRequest>()
+            .add_event::<WindowCommand>()
             .add_event::<WindowClosed>()
             .add_event::<WindowResized>()
+            .add_event::<WindowMoved>()
+            .add_event::<WindowScaleFactorChanged>()
+            .add_event::<WindowThemeChanged>()
+            .add_event::<FileDragAndDrop>()
             .add_event::<WindowKeyboardInput>()
+            .add_event::<WindowCursorMoved>()
+            .add_event::<WindowCursorEntered>()
+            .add_event::<WindowCursorLeft>()
+            .add_event::<WindowMouseButtonInput>()
+            .add_event::<WindowMouseWheel>()
+            .add_event::<MouseMotion>()
+            .add_event::<WindowModifiersChanged>()
+            .add_event::<WindowFocused>()
+            .add_event::<WindowCharacterInput>()
+            .add_event::<WindowIme>()
+            .add_resource(Input::<VirtualKeyCode>::default())
+            .add_resource(Input::<ScanCode>::default())
+            .add_resource(Input::<MouseButton>::default())
+            .add_resource(ModifiersState::default())
             .add_system(handle_window_event_sys())
+            .add_system(input_sys())
+            .add_system(window_state_sys())
             .set_runner(window_runner);
 
         if let Some(descriptor) = self.initial_window.take() {
-            app.resources
-                .get_mut::<Events<WindowCreateRequest>>()
-                .unwrap()
-                .send(WindowCreateRequest { descriptor })
+            app.world
+                .spawn()
+                .add(Window::new(descriptor))
+                .add(PrimaryWindow);
         }
     }
 }
@@ -1,16 +1,26 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    path::Path,
+};
 
+use app::Entity;
 use winit::{
-    dpi::PhysicalSize,
+    dpi::{PhysicalPosition, PhysicalSize},
     event_loop::EventLoopWindowTarget,
-    window::{Window, WindowBuilder, WindowId},
+    monitor::{MonitorHandle, VideoMode},
+    window::{CursorGrabMode, Fullscreen, Icon, Window, WindowBuilder, WindowId},
 };
 
-use crate::WindowDescriptor;
+use crate::{WindowCommandKind, WindowDescriptor, WindowMode};
 
 #[derive(Default)]
 pub struct WindowManager {
     windows: HashMap<WindowId, Window>,
+    descriptors: HashMap<WindowId, WindowDescriptor>,
+    entities: HashMap<WindowId, Entity>,
+    primary: Option<WindowId>,
+    ime_allowed: HashSet<WindowId>,
 }
 
 impl WindowManager {
@@ -23,22 +33,191 @@ impl WindowManager {
         &mut self,
         event_loop: &EventLoopWindowTarget<()>,
         descriptor: WindowDescriptor,
+        entity: Entity,
     ) -> WindowId {
-        let builder = WindowBuilder::new()
+        let mut builder = WindowBuilder::new()
             .with_inner_size(PhysicalSize::new(descriptor.width, descriptor.height))
-            .with_title(descriptor.title);
+            .with_title(&descriptor.title)
+            .with_resizable(descriptor.resizable)
+            .with_decorations(descriptor.decorations)
+            .with_transparent(descriptor.transparent)
+            .with_always_on_top(descriptor.always_on_top);
+        if let Some((width, height)) = descriptor.min_size {
+            builder = builder.with_min_inner_size(PhysicalSize::new(width, height));
+        }
+        if let Some((width, height)) = descriptor.max_size {
+            builder = builder.with_max_inner_size(PhysicalSize::new(width, height));
+        }
+        if let Some((x, y)) = descriptor.position {
+            builder = builder.with_position(PhysicalPosition::new(x, y));
+        }
+        if let Some(path) = &descriptor.icon {
+            match load_icon(path) {
+                Ok(icon) => builder = builder.with_window_icon(Some(icon)),
+                Err(err) => tracing::warn!(path = ?path, "failed to load window icon: {}", err),
+            }
+        }
+        builder = builder.with_fullscreen(fullscreen(
+            descriptor.mode,
+            event_loop.primary_monitor(),
+            &descriptor,
+        ));
 
         let window = builder.build(event_loop).unwrap();
+        window.set_cursor_visible(descriptor.cursor_visible);
         let window_id = window.id();
         self.windows.insert(window_id, window);
+        self.descriptors.insert(window_id, descriptor);
+        self.entities.insert(window_id, entity);
         window_id
     }
 
+    pub fn ids(&self) -> impl Iterator<Item = WindowId> + '_ {
+        self.windows.keys().copied()
+    }
+
+    /// Window of the entity with `PrimaryWindow`, if it is open.
+    pub fn primary(&self) -> Option<WindowId> {
+        self.primary
+    }
+
+    pub(crate) fn set_primary(&mut self, id: WindowId) {
+        self.primary = Some(id);
+    }
+
+    /// Entity holding the `Window` component of the window.
+    pub fn entity(&self, id: &WindowId) -> Option<Entity> {
+        self.entities.get(id).copied()
+    }
+
+    /// Descriptor the window was created with.
+    pub fn descriptor(&self, id: &WindowId) -> Option<&WindowDescriptor> {
+        self.descriptors.get(id)
+    }
+
     pub fn get(&self, id: &WindowId) -> Option<&Window> {
         self.windows.get(id)
     }
 
     pub fn remove(&mut self, id: &WindowId) -> Option<Window> {
+        self.ime_allowed.remove(id);
+        self.descriptors.remove(id);
+        self.entities.remove(id);
+        if self.primary == Some(*id) {
+            self.primary = None;
+        }
         self.windows.remove(id)
     }
+
+    /// Apply a command to a window, the descriptor is kept in sync.
+    pub fn apply(&mut self, id: &WindowId, command: &WindowCommandKind) {
+        let (window, descriptor) = match (self.windows.get(id), self.descriptors.get_mut(id)) {
+            (Some(window), Some(descriptor)) => (window, descriptor),
+            _ => return,
+        };
+
+        command.update_descriptor(descriptor);
+        match command {
+            WindowCommandKind::SetTitle(title) => window.set_title(title),
+            &WindowCommandKind::SetSize { width, height } => {
+                window.set_inner_size(PhysicalSize::new(width, height))
+            }
+            &WindowCommandKind::SetPosition { x, y } => {
+                window.set_outer_position(PhysicalPosition::new(x, y))
+            }
+            &WindowCommandKind::SetMode(mode) => {
+                window.set_fullscreen(fullscreen(mode, window.current_monitor(), descriptor))
+            }
+            &WindowCommandKind::SetCursorGrab(grab) => {
+                // Platforms support either confining or locking the cursor.
+                let result = match grab {
+                    true => window
+                        .set_cursor_grab(CursorGrabMode::Confined)
+                        .or_else(|_| window.set_cursor_grab(CursorGrabMode::Locked)),
+                    false => window.set_cursor_grab(CursorGrabMode::None),
+                };
+                if let Err(err) = result {
+                    tracing::warn!(?id, "failed to set cursor grab: {}", err);
+                }
+            }
+            &WindowCommandKind::SetCursorVisible(visible) => window.set_cursor_visible(visible),
+            &WindowCommandKind::SetCursorIcon(icon) => window.set_cursor_icon(icon),
+            &WindowCommandKind::SetMinimized(minimized) => window.set_minimized(minimized),
+            &WindowCommandKind::SetMaximized(maximized) => window.set_maximized(maximized),
+            &WindowCommandKind::SetImeAllowed(allowed) => self.set_ime_allowed(id, allowed),
+            &WindowCommandKind::SetImePosition { x, y } => {
+                window.set_ime_position(PhysicalPosition::new(x, y))
+            }
+            WindowCommandKind::RequestRedraw => window.request_redraw(),
+        }
+    }
+
+    /// Whether the input method is allowed on the window, disallowed by
+    /// default.
+    pub fn ime_allowed(&self, id: &WindowId) -> bool {
+        self.ime_allowed.contains(id)
+    }
+
+    /// Allow the input method on the window, such as while a text field has
+    /// focus. `WindowIme` events are only sent while it is allowed.
+    pub fn set_ime_allowed(&mut self, id: &WindowId, allowed: bool) {
+        let window = match self.windows.get(id) {
+            Some(window) => window,
+            None => return,
+        };
+        window.set_ime_allowed(allowed);
+        if allowed {
+            self.ime_allowed.insert(*id);
+        } else {
+            self.ime_allowed.remove(id);
+        }
+    }
+
+    /// Position of the IME candidate window in physical pixels, usually right
+    /// under the text being edited.
+    pub fn set_ime_position(&self, id: &WindowId, x: i32, y: i32) {
+        if let Some(window) = self.windows.get(id) {
+            window.set_ime_position(PhysicalPosition::new(x, y));
+        }
+    }
+}
+
+fn load_icon(path: &Path) -> Result<Icon, Box<dyn Error>> {
+    let image = image::open(path)?.into_rgba8();
+    let (width, height) = image.dimensions();
+    Ok(Icon::from_rgba(image.into_raw(), width, height)?)
+}
+
+fn fullscreen(
+    mode: WindowMode,
+    monitor: Option<MonitorHandle>,
+    descriptor: &WindowDescriptor,
+) -> Option<Fullscreen> {
+    match mode {
+        WindowMode::Windowed => None,
+        WindowMode::BorderlessFullscreen => Some(Fullscreen::Borderless(monitor)),
+        WindowMode::Fullscreen => Some(
+            monitor
+                .and_then(|monitor| closest_video_mode(monitor.video_modes(), descriptor))
+                .map_or(Fullscreen::Borderless(None), Fullscreen::Exclusive),
+        ),
+    }
+}
+
+/// Video mode with the size closest to the descriptor, then the highest
+/// refresh rate and bit depth.
+fn closest_video_mode(
+    modes: impl Iterator<Item = VideoMode>,
+    descriptor: &WindowDescriptor,
+) -> Option<VideoMode> {
+    modes.min_by_key(|mode| {
+        let size = mode.size();
+        let distance = (size.width as i64 - descriptor.width as i64).abs()
+            + (size.height as i64 - descriptor.height as i64).abs();
+        (
+            distance,
+            std::cmp::Reverse(mode.refresh_rate_millihertz()),
+            std::cmp::Reverse(mode.bit_depth()),
+        )
+    })
 }
@@ -1,12 +1,20 @@
-use app::{App, AppExit, AppStage, EventReader, Events, ParRunnable, Resources, SystemBuilder};
+use std::collections::{HashMap, HashSet};
+
+use app::{
+    App, AppExit, AppStage, Entities, EventReader, Events, ParRunnable, Query, SystemBuilder,
+};
 use winit::{
     event::{self, Event},
     event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    window::WindowId,
 };
 
 use crate::{
-    manager::WindowManager, WindowCloseRequest, WindowClosed, WindowCreateRequest, WindowCreated,
-    WindowKeyboardInput, WindowResized,
+    manager::WindowManager, ExitCondition, FileDragAndDrop, MouseMotion, PrimaryWindow, Window,
+    WindowCharacterInput, WindowClosePolicy, WindowCloseRequest, WindowClosed, WindowCommand,
+    WindowCreateRequest, WindowCreated, WindowCursorEntered, WindowCursorLeft, WindowCursorMoved,
+    WindowFocused, WindowIme, WindowKeyboardInput, WindowModifiersChanged, WindowMouseButtonInput,
+    WindowMouseWheel, WindowMoved, WindowResized, WindowScaleFactorChanged, WindowThemeChanged,
 };
 
 pub fn window_runner(mut app: App) {
@@ -14,23 +22,25 @@ pub fn window_runner(mut app: App) {
 
     let mut app_exit_event_reader = EventReader::<AppExit>::default();
     let mut window_create_request_reader = EventReader::<WindowCreateRequest>::default();
+    let mut window_command_reader = EventReader::<WindowCommand>::default();
 
     let mut active = true;
+    let mut exit_code = 0;
 
     event_loop.run(move |event, event_loop, control_flow| {
         *control_flow = ControlFlow::Poll;
 
-        if app_exit_event_reader
+        if let Some(exit) = app_exit_event_reader
             .iter(&app.resources.get::<Events<AppExit>>().unwrap())
             .next_back()
-            .is_some()
         {
+            exit_code = exit.code;
             *control_flow = ControlFlow::Exit;
         }
 
         match event {
             Event::WindowEvent { window_id, event } => {
-                let manager = app.resources.get_mut::<WindowManager>().unwrap();
+                let manager = app.resources.get::<WindowManager>().unwrap();
                 if manager.get(&window_id).is_none() {
                     return;
                 }
@@ -50,15 +60,69 @@ pub fn window_runner(mut app: App) {
                             width: size.width,
                             height: size.height,
                         }),
-                    event::WindowEvent::ScaleFactorChanged { new_inner_size, .. } => app
+                    event::WindowEvent::ScaleFactorChanged {
+                        scale_factor,
+                        new_inner_size,
+                    } => {
+                        let logical_size = new_inner_size.to_logical::<f64>(scale_factor);
+                        app.resources
+                            .get_mut::<Events<WindowScaleFactorChanged>>()
+                            .unwrap()
+                            .send(WindowScaleFactorChanged {
+                                id: window_id,
+                                scale_factor,
+                                width: new_inner_size.width,
+                                height: new_inner_size.height,
+                                logical_width: logical_size.width,
+                                logical_height: logical_size.height,
+                            });
+                        app.resources
+                            .get_mut::<Events<WindowResized>>()
+                            .unwrap()
+                            .send(WindowResized {
+                                id: window_id,
+                                width: new_inner_size.width,
+                                height: new_inner_size.height,
+                            })
+                    }
+                    event::WindowEvent::Moved(position) => app
                         .resources
-                        .get_mut::<Events<WindowResized>>()
+                        .get_mut::<Events<WindowMoved>>()
                         .unwrap()
-                        .send(WindowResized {
+                        .send(WindowMoved {
                             id: window_id,
-                            width: new_inner_size.width,
-                            height: new_inner_size.height,
+                            x: position.x,
+                            y: position.y,
                         }),
+                    event::WindowEvent::ThemeChanged(theme) => app
+                        .resources
+                        .get_mut::<Events<WindowThemeChanged>>()
+                        .unwrap()
+                        .send(WindowThemeChanged {
+                            id: window_id,
+                            theme,
+                        }),
+                    event::WindowEvent::HoveredFile(path) => app
+                        .resources
+                        .get_mut::<Events<FileDragAndDrop>>()
+                        .unwrap()
+                        .send(FileDragAndDrop::HoveredFile {
+                            id: window_id,
+                            path,
+                        }),
+                    event::WindowEvent::DroppedFile(path) => app
+                        .resources
+                        .get_mut::<Events<FileDragAndDrop>>()
+                        .unwrap()
+                        .send(FileDragAndDrop::DroppedFile {
+                            id: window_id,
+                            path,
+                        }),
+                    event::WindowEvent::HoveredFileCancelled => app
+                        .resources
+                        .get_mut::<Events<FileDragAndDrop>>()
+                        .unwrap()
+                        .send(FileDragAndDrop::HoveredFileCancelled { id: window_id }),
                     event::WindowEvent::KeyboardInput { ref input, .. } => app
                         .resources
                         .get_mut::<Events<WindowKeyboardInput>>()
@@ -68,9 +132,88 @@ pub fn window_runner(mut app: App) {
                             scan_code: input.scancode,
                             state: input.state,
                         }),
+                    event::WindowEvent::ReceivedCharacter(character) => app
+                        .resources
+                        .get_mut::<Events<WindowCharacterInput>>()
+                        .unwrap()
+                        .send(WindowCharacterInput {
+                            id: window_id,
+                            character,
+                        }),
+                    event::WindowEvent::Ime(ime) => app
+                        .resources
+                        .get_mut::<Events<WindowIme>>()
+                        .unwrap()
+                        .send(WindowIme { id: window_id, ime }),
+                    event::WindowEvent::CursorMoved { position, .. } => app
+                        .resources
+                        .get_mut::<Events<WindowCursorMoved>>()
+                        .unwrap()
+                        .send(WindowCursorMoved {
+                            id: window_id,
+                            x: position.x,
+                            y: position.y,
+                        }),
+                    event::WindowEvent::CursorEntered { .. } => app
+                        .resources
+                        .get_mut::<Events<WindowCursorEntered>>()
+                        .unwrap()
+                        .send(WindowCursorEntered { id: window_id }),
+                    event::WindowEvent::CursorLeft { .. } => app
+                        .resources
+                        .get_mut::<Events<WindowCursorLeft>>()
+                        .unwrap()
+                        .send(WindowCursorLeft { id: window_id }),
+                    event::WindowEvent::MouseInput { state, button, .. } => app
+                        .resources
+                        .get_mut::<Events<WindowMouseButtonInput>>()
+                        .unwrap()
+                        .send(WindowMouseButtonInput {
+                            id: window_id,
+                            button,
+                            state,
+                        }),
+                    event::WindowEvent::MouseWheel { delta, .. } => app
+                        .resources
+                        .get_mut::<Events<WindowMouseWheel>>()
+                        .unwrap()
+                        .send(WindowMouseWheel {
+                            id: window_id,
+                            delta,
+                        }),
+                    event::WindowEvent::ModifiersChanged(modifiers) => app
+                        .resources
+                        .get_mut::<Events<WindowModifiersChanged>>()
+                        .unwrap()
+                        .send(WindowModifiersChanged {
+                            id: window_id,
+                            modifiers,
+                        }),
+                    event::WindowEvent::Focused(focused) => app
+                        .resources
+                        .get_mut::<Events<WindowFocused>>()
+                        .unwrap()
+                        .send(WindowFocused {
+                            id: window_id,
+                            focused,
+                        }),
                     _ => {}
                 }
             }
+            Event::DeviceEvent {
+                event: event::DeviceEvent::MouseMotion { delta: (dx, dy) },
+                ..
+            } => app
+                .resources
+                .get_mut::<Events<MouseMotion>>()
+                .unwrap()
+                .send(MouseMotion { dx, dy }),
+            Event::LoopDestroyed => {
+                app.shutdown();
+                if exit_code != 0 {
+                    std::process::exit(exit_code);
+                }
+            }
             Event::Suspended => {
                 active = false;
             }
@@ -78,11 +221,8 @@ pub fn window_runner(mut app: App) {
                 active = true;
             }
             Event::MainEventsCleared => {
-                handle_create_window_event(
-                    &mut app.resources,
-                    event_loop,
-                    &mut window_create_request_reader,
-                );
+                sync_windows(&mut app, event_loop, &mut window_create_request_reader);
+                handle_window_commands(&mut app, &mut window_command_reader);
 
                 if active {
                     app.update()
@@ -93,42 +233,157 @@ pub fn window_runner(mut app: App) {
     });
 }
 
-fn handle_create_window_event(
-    resources: &mut Resources,
+/// Create the OS windows of new `Window` entities and of `WindowCreateRequest`
+/// events, close the windows whose entity is gone.
+fn sync_windows(
+    app: &mut App,
     event_loop: &EventLoopWindowTarget<()>,
     event_reader: &mut EventReader<WindowCreateRequest>,
 ) {
-    let mut manager = resources.get_mut::<WindowManager>().unwrap();
-    let window_create_request_event = resources.get::<Events<WindowCreateRequest>>().unwrap();
+    for event in event_reader.iter(&app.resources.get::<Events<WindowCreateRequest>>().unwrap()) {
+        app.world.spawn().add(Window::new(event.descriptor.clone()));
+    }
+
+    let query = Query::<(Entities, &mut Window, Option<&PrimaryWindow>)>::new();
+    let windows = query.iter(&app.world);
+    let live: HashSet<WindowId> = windows
+        .iter()
+        .filter_map(|(_, window, _)| window.id())
+        .collect();
+    let closed: Vec<WindowId> = app
+        .resources
+        .get::<WindowManager>()
+        .unwrap()
+        .ids()
+        .filter(|id| !live.contains(id))
+        .collect();
+    let pending = windows.iter().any(|(_, window, _)| window.id().is_none());
+    if !pending && closed.is_empty() {
+        return;
+    }
+
+    let mut manager = app.resources.get_mut::<WindowManager>().unwrap();
+    let mut created_events = app.resources.get_mut::<Events<WindowCreated>>().unwrap();
+    for (entity, window, primary) in windows {
+        if window.id().is_some() {
+            continue;
+        }
+        let id = manager.create(event_loop, window.descriptor().clone(), entity);
+        if primary.is_some() {
+            manager.set_primary(id);
+        }
+        let os_window = manager.get(&id).unwrap();
+        let size = os_window.inner_size();
+        window.created(id, size.width, size.height, os_window.scale_factor());
+        created_events.send(WindowCreated { id, entity });
+    }
 
-    for event in event_reader.iter(&window_create_request_event) {
-        let id = manager.create(event_loop, event.descriptor.clone());
-        resources
-            .get_mut::<Events<WindowCreated>>()
+    if closed.is_empty() {
+        return;
+    }
+    let primary = manager.primary();
+    let mut closed_events = app.resources.get_mut::<Events<WindowClosed>>().unwrap();
+    for &id in closed.iter() {
+        manager.remove(&id);
+        closed_events.send(WindowClosed { id });
+    }
+    let exit = match app.resources.get::<WindowClosePolicy>().unwrap().exit {
+        ExitCondition::OnPrimaryClosed => primary.is_some_and(|id| closed.contains(&id)),
+        ExitCondition::OnAllClosed => manager.len() == 0,
+        ExitCondition::DontExit => false,
+    };
+    if exit {
+        app.resources
+            .get_mut::<Events<AppExit>>()
             .unwrap()
-            .send(WindowCreated { id })
+            .send(AppExit::default());
+    }
+}
+
+fn handle_window_commands(app: &mut App, event_reader: &mut EventReader<WindowCommand>) {
+    let window_command_event = app.resources.get::<Events<WindowCommand>>().unwrap();
+    let mut commands = event_reader.iter(&window_command_event).peekable();
+    if commands.peek().is_none() {
+        return;
+    }
+
+    let commands = commands.collect::<Vec<_>>();
+    let mut manager = app.resources.get_mut::<WindowManager>().unwrap();
+    for event in commands.iter() {
+        manager.apply(&event.id, &event.command);
+    }
+
+    // Only the commanded fields change, the component also follows the events
+    // the manager does not see, such as the user moving the window.
+    let query = Query::<&mut Window>::new();
+    let mut windows = query
+        .iter(&app.world)
+        .into_iter()
+        .filter_map(|window| Some((window.id()?, window)))
+        .collect::<HashMap<_, _>>();
+    for event in commands {
+        if let Some(window) = windows.get_mut(&event.id) {
+            event.command.update_descriptor(window.descriptor_mut());
+        }
     }
 }
 
+/// Despawn the entity of windows requested to close, following
+/// `WindowClosePolicy`. The window is closed before the next update.
 pub(crate) fn handle_window_event_sys() -> impl ParRunnable {
     let mut window_close_request_reader = EventReader::<WindowCloseRequest>::default();
 
     SystemBuilder::new()
         .on_stage(AppStage::Begin)
-        .write_resource::<WindowManager>()
-        .write_resource::<Events<WindowCloseRequest>>()
-        .write_resource::<Events<WindowClosed>>()
-        .write_resource::<Events<AppExit>>()
-        .build(
-            move |_, _, (manager, close_request, closed_event, app_exit), _| {
-                for event in window_close_request_reader.iter(&close_request) {
-                    if manager.remove(&event.id).is_some() {
-                        closed_event.send(WindowClosed { id: event.id });
-                        if manager.len() <= 0 {
-                            app_exit.send(AppExit);
-                        }
-                    }
+        .read_non_send_resource::<WindowManager>()
+        .read_resource::<WindowClosePolicy>()
+        .read_resource::<Events<WindowCloseRequest>>()
+        .build(move |_, cmd, (manager, policy, close_request), _| {
+            for event in window_close_request_reader.iter(&close_request) {
+                if !policy.close_on_request {
+                    continue;
                 }
-            },
-        )
+                if let Some(entity) = manager.entity(&event.id) {
+                    cmd.despawn(entity);
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{WindowCommandKind, WindowDescriptor, WindowPlugin};
+
+    use super::*;
+
+    #[test]
+    fn commands_keep_window_state() {
+        let mut app = App::new();
+        app.add_plugin(WindowPlugin::default());
+        let id = unsafe { WindowId::dummy() };
+        let mut window = Window::new(WindowDescriptor::default());
+        window.created(id, 800, 600, 1.);
+        app.world.spawn().add(window);
+
+        app.resources
+            .get_mut::<Events<WindowMoved>>()
+            .unwrap()
+            .send(WindowMoved { id, x: 40, y: 30 });
+        app.update();
+
+        let mut reader = EventReader::<WindowCommand>::default();
+        app.resources
+            .get_mut::<Events<WindowCommand>>()
+            .unwrap()
+            .send(WindowCommand {
+                id,
+                command: WindowCommandKind::SetTitle("renamed".to_string()),
+            });
+        handle_window_commands(&mut app, &mut reader);
+
+        let query = Query::<&Window>::new();
+        let windows = query.iter(&app.world);
+        assert_eq!(windows[0].descriptor().title, "renamed");
+        assert_eq!(windows[0].descriptor().position, Some((40, 30)));
+    }
 }
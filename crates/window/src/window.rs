@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use app::{AppStage, EventReader, Events, ParRunnable, Query, SystemBuilder};
+use winit::window::WindowId;
+
+use crate::{
+    WindowCursorLeft, WindowCursorMoved, WindowDescriptor, WindowFocused, WindowMoved,
+    WindowResized, WindowScaleFactorChanged,
+};
+
+/// Window component. Spawning an entity with it creates the OS window before
+/// the next update, despawning the entity closes it.
+///
+/// The component mirrors the window state, change the window with
+/// `WindowCommand`.
+#[derive(Debug, Clone)]
+pub struct Window {
+    descriptor: WindowDescriptor,
+    id: Option<WindowId>,
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+    focused: bool,
+    cursor_position: Option<(f64, f64)>,
+}
+
+impl Window {
+    pub fn new(descriptor: WindowDescriptor) -> Self {
+        Window {
+            width: descriptor.width,
+            height: descriptor.height,
+            descriptor,
+            id: None,
+            scale_factor: 1.,
+            focused: false,
+            cursor_position: None,
+        }
+    }
+
+    #[inline]
+    pub fn descriptor(&self) -> &WindowDescriptor {
+        &self.descriptor
+    }
+
+    /// `None` until the OS window is created.
+    #[inline]
+    pub fn id(&self) -> Option<WindowId> {
+        self.id
+    }
+
+    /// Inner width in physical pixels.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Inner height in physical pixels.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    #[inline]
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Cursor position in physical pixels, `None` when the cursor is outside the
+    /// window.
+    #[inline]
+    pub fn cursor_position(&self) -> Option<(f64, f64)> {
+        self.cursor_position
+    }
+
+    pub(crate) fn created(&mut self, id: WindowId, width: u32, height: u32, scale_factor: f64) {
+        self.id = Some(id);
+        self.width = width;
+        self.height = height;
+        self.scale_factor = scale_factor;
+    }
+
+    pub(crate) fn descriptor_mut(&mut self) -> &mut WindowDescriptor {
+        &mut self.descriptor
+    }
+}
+
+/// Marker of the main window of the app, see `ExitCondition::OnPrimaryClosed`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrimaryWindow;
+
+pub(crate) fn window_state_sys() -> impl ParRunnable {
+    let mut resized_reader = EventReader::<WindowResized>::default();
+    let mut scale_factor_reader = EventReader::<WindowScaleFactorChanged>::default();
+    let mut focused_reader = EventReader::<WindowFocused>::default();
+    let mut moved_reader = EventReader::<WindowMoved>::default();
+    let mut cursor_moved_reader = EventReader::<WindowCursorMoved>::default();
+    let mut cursor_left_reader = EventReader::<WindowCursorLeft>::default();
+
+    SystemBuilder::new()
+        .on_stage(AppStage::PreUpdate)
+        .read_resource::<Events<WindowResized>>()
+        .read_resource::<Events<WindowScaleFactorChanged>>()
+        .read_resource::<Events<WindowFocused>>()
+        .read_resource::<Events<WindowMoved>>()
+        .read_resource::<Events<WindowCursorMoved>>()
+        .read_resource::<Events<WindowCursorLeft>>()
+        .with_query(Query::<&mut Window>::new())
+        .build(move |world, _, resources, query| {
+            let (resized, scale_factor, focused, moved, cursor_moved, cursor_left) = resources;
+            let mut windows = query
+                .iter(world)
+                .into_iter()
+                .filter_map(|window| Some((window.id?, window)))
+                .collect::<HashMap<_, _>>();
+            for event in resized_reader.iter(resized) {
+                if let Some(window) = windows.get_mut(&event.id) {
+                    window.width = event.width;
+                    window.height = event.height;
+                }
+            }
+            for event in scale_factor_reader.iter(scale_factor) {
+                if let Some(window) = windows.get_mut(&event.id) {
+                    window.scale_factor = event.scale_factor;
+                }
+            }
+            for event in focused_reader.iter(focused) {
+                if let Some(window) = windows.get_mut(&event.id) {
+                    window.focused = event.focused;
+                }
+            }
+            for event in moved_reader.iter(moved) {
+                if let Some(window) = windows.get_mut(&event.id) {
+                    window.descriptor.position = Some((event.x, event.y));
+                }
+            }
+            for event in cursor_moved_reader.iter(cursor_moved) {
+                if let Some(window) = windows.get_mut(&event.id) {
+                    window.cursor_position = Some((event.x, event.y));
+                }
+            }
+            for event in cursor_left_reader.iter(cursor_left) {
+                if let Some(window) = windows.get_mut(&event.id) {
+                    window.cursor_position = None;
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use app::{App, Entities};
+
+    use crate::WindowPlugin;
+
+    use super::*;
+
+    #[test]
+    fn window_state() {
+        let mut app = App::new();
+        app.add_plugin(WindowPlugin::default());
+        let id = unsafe { WindowId::dummy() };
+        let mut window = Window::new(WindowDescriptor::default());
+        window.created(id, 800, 600, 1.);
+        let entity = app.world.spawn().add(window).entity();
+
+        app.resources
+            .get_mut::<Events<WindowResized>>()
+            .unwrap()
+            .send(WindowResized {
+                id,
+                width: 1600,
+                height: 1200,
+            });
+        app.resources
+            .get_mut::<Events<WindowScaleFactorChanged>>()
+            .unwrap()
+            .send(WindowScaleFactorChanged {
+                id,
+                scale_factor: 2.,
+                width: 1600,
+                height: 1200,
+                logical_width: 800.,
+                logical_height: 600.,
+            });
+        app.resources
+            .get_mut::<Events<WindowFocused>>()
+            .unwrap()
+            .send(WindowFocused { id, focused: true });
+        app.resources
+            .get_mut::<Events<WindowCursorMoved>>()
+            .unwrap()
+            .send(WindowCursorMoved { id, x: 10., y: 20. });
+        app.update();
+
+        let query = Query::<(Entities, &Window)>::new();
+        let windows = query.iter(&app.world);
+        let (window_entity, window) = windows[0];
+        assert_eq!(window_entity, entity);
+        assert_eq!((window.width(), window.height()), (1600, 1200));
+        assert_eq!(window.scale_factor(), 2.);
+        assert!(window.focused());
+        assert_eq!(window.cursor_position(), Some((10., 20.)));
+    }
+}
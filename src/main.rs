@@ -1,43 +1,41 @@
-use app::{App, AppStage, EventReader, Events, ParRunnable, SystemBuilder};
+use app::{App, AppStage, Events, ParRunnable, SystemBuilder};
 use render_plugin::RenderPlugin;
 use window_plugin::{
-    winit::event::{ElementState, VirtualKeyCode},
-    WindowCreateRequest, WindowDescriptor, WindowKeyboardInput, WindowPlugin,
+    winit::event::VirtualKeyCode, ActionMap, ActionPlugin, ActionState, WindowCreateRequest,
+    WindowDescriptor, WindowPlugin,
 };
 
-fn create_window() -> impl ParRunnable {
-    let mut event_reader = EventReader::<WindowKeyboardInput>::default();
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Action {
+    NewWindow,
+}
 
+fn create_window() -> impl ParRunnable {
     SystemBuilder::new()
-        .on_stage(AppStage::PreUpdate)
-        .read_resource::<Events<WindowKeyboardInput>>()
+        .on_stage(AppStage::Update)
+        .read_resource::<ActionState<Action>>()
         .write_resource::<Events<WindowCreateRequest>>()
-        .build(
-            move |_, _, (keyboard_input_events, window_create_events), _| {
-                for event in event_reader.iter(keyboard_input_events) {
-                    if event.key_code == Some(VirtualKeyCode::A)
-                        && event.state == ElementState::Released
-                    {
-                        window_create_events.send(WindowCreateRequest {
-                            descriptor: WindowDescriptor {
-                                width: 550,
-                                height: 400,
-                                title: "Another window".to_string(),
-                            },
-                        })
-                    }
-                }
-            },
-        )
+        .build(|_, _, (actions, window_create_events), _| {
+            if actions.just_released(Action::NewWindow) {
+                window_create_events.send(WindowCreateRequest {
+                    descriptor: WindowDescriptor {
+                        width: 550,
+                        height: 400,
+                        title: "Another window".to_string(),
+                        ..Default::default()
+                    },
+                })
+            }
+        })
 }
 
 fn main() {
+    let mut actions = ActionMap::new();
+    actions.bind(Action::NewWindow, VirtualKeyCode::A);
+
     App::new()
-        .add_plugin(WindowPlugin::with_initial(WindowDescriptor {
-            width: 800,
-            height: 600,
-            title: "WiGame".to_string(),
-        }))
+        .add_plugin(WindowPlugin::with_initial(WindowDescriptor::default()))
+        .add_plugin(ActionPlugin::new(actions))
         .add_plugin(RenderPlugin::default())
         .add_system(create_window())
         .run();